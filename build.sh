@@ -2,6 +2,7 @@
 
 echo "building tinyTetris in $(pwd)"
 
-cargo build --release --target target.json -Zjson-target-spec
+cargo build --release --target target.json -Zjson-target-spec \
+    -Zbuild-std=core,compiler_builtins,alloc -Zbuild-std-features=compiler-builtins-mem
 
 echo "tinyTetris built"
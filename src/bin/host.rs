@@ -0,0 +1,458 @@
+//! tinyTetris in an ordinary Linux terminal, for trying out changes without booting tinyOS.
+//!
+//! Run with `cargo run --no-default-features --features std --bin tinyTetris-host`, quit with `q`.
+//! `--renderer=memory` draws every frame into an in-memory framebuffer instead of the terminal.
+//! `--theme=<name or file>` picks one of the built-in themes or reads a theme file, `--patterns`
+//! draws a pattern per piece kind into the blocks, `--style=<name>` picks the block style,
+//! `--grid` shows a grid on the empty board and `--no-animations` turns off all animations.
+//! `s` saves the frame into the working directory, as text for the terminal renderer and as PPM or
+//! with `--screenshot=bmp` as BMP for the memory renderer.
+//! `--keys=<file>` reads key bindings from a key file, `b` changes them while playing and saves
+//! them back into that file. `--das=`, `--arr=` and `--sdf=` set the delayed auto shift and auto
+//! repeat rate in milliseconds and the soft-drop factor. `--record=<file>` saves the input of the
+//! session as a replay file when quitting, `--replay=<file>` plays one back instead of reading
+//! keys, with the keys, handling and animations it was recorded with.
+
+use std::{
+    fmt,
+    io::{self, Write},
+    path::Path,
+    process::ExitCode,
+    sync::{Mutex, OnceLock, PoisonError},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use tiny_tetris::{
+    Action, Error, Event, GameState, Renderer, crash,
+    embedded_graphics::prelude::Size,
+    interface::{
+        Key, KeyCode, Modifiers, QUIT, REBIND, SCREENSHOT,
+        bindings::{self, Bindings, Rebinding},
+        decode::Decoder,
+        handling::{AutoRepeat, Handling},
+        pacing::Pacer,
+        source::{Frame, InputError, InputSource, Recorder, Replay, Setup},
+    },
+    render::{
+        Appearance,
+        animation::{LINE_CLEAR, TOP_OUT},
+        ansi::AnsiRenderer,
+        memory::MemoryBuffer,
+        pixel::PixelRenderer,
+        screenshot::{self, Format},
+        style::BlockStyle,
+        theme::Theme,
+    },
+};
+
+// milliseconds per frame, the game advances this often whether or not keys are pressed
+const FRAME_TIME: u32 = 16;
+// raw mode delivers ctrl-c as a key instead of a signal
+const CTRL_C: Key = Key::new(KeyCode::Char('c'), Modifiers::CTRL);
+const MEMORY_SIZE: Size = Size::new(640, 480);
+
+// the state after the last event, what the crash report shows
+static LAST_STATE: Mutex<Option<GameState>> = Mutex::new(None);
+// the terminal mode before raw mode, restored by the crash hook
+static ORIGINAL_MODE: OnceLock<libc::termios> = OnceLock::new();
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("tinyTetris: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), Error> {
+    let mut renderer = renderer_from_args()?;
+    let mut appearance = appearance_from_args()?;
+    let format = screenshot_format_from_args()?;
+    let keys_file = arg("--keys=");
+    let replay = arg("--replay=")
+        .map(|path| replay_from_file(&path))
+        .transpose()?;
+    let setup = match &replay {
+        // a replay plays out the recorded game, whatever the arguments say
+        Some(replay) => replay.setup().clone(),
+        None => Setup {
+            seed: seed(),
+            bindings: match &keys_file {
+                Some(path) => bindings_from_file(path)?,
+                None => Bindings::default(),
+            },
+            handling: handling_from_args()?,
+            animations: appearance.animations,
+        },
+    };
+    appearance.animations = setup.animations;
+    renderer.set_appearance(appearance);
+    install_crash_hook(appearance);
+    let source: Box<dyn InputSource> = match replay {
+        Some(replay) => Box::new(RealTime(replay)),
+        None => Box::new(TerminalInput::new().map_err(|err| InputError::Open(err.to_string()))?),
+    };
+    let mut input = Recorder::new(source, &setup);
+
+    let mut state = GameState::new(setup.seed);
+    if setup.animations {
+        state.set_line_clear_delay(LINE_CLEAR);
+    }
+    remember(&state);
+    // the game clock when the last game was lost
+    let mut over_at = None;
+    let mut rebinding: Option<Rebinding> = None;
+    let mut bindings = setup.bindings;
+    let mut auto_repeat = AutoRepeat::new(setup.handling);
+    let mut play = || -> Result<(), Error> {
+        while let Some(Frame { mut keys, dt }) = input.next_frame()? {
+            if keys.contains(&CTRL_C) {
+                return Ok(());
+            }
+            let mut actions = Vec::new();
+            if let Some(screen) = &mut rebinding {
+                // the rebinding screen takes every key
+                keys.drain(..).for_each(|key| screen.press(key));
+            }
+            if let Some(done) = rebinding.take_if(|screen| screen.action().is_none()) {
+                bindings = done.into_bindings();
+                if let Some(path) = &keys_file
+                    && let Err(err) = std::fs::write(path, bindings.to_file())
+                {
+                    eprint!("saving {path} failed: {err}\r\n");
+                    renderer.invalidate();
+                }
+            }
+            if keys.contains(&QUIT) {
+                return Ok(());
+            }
+            if keys.contains(&REBIND) {
+                rebinding = Some(Rebinding::new(bindings.clone()));
+                keys.clear();
+                if !state.is_paused() {
+                    actions.push(Action::Pause);
+                }
+            }
+            if keys.contains(&SCREENSHOT)
+                && let Some(shot) = renderer.screenshot()
+            {
+                let extension = shot.extension(format);
+                let path = screenshot::file_name(extension, |name| Path::new(name).exists());
+                if let Err(err) = std::fs::write(&path, shot.encode(format)) {
+                    eprint!("saving {path} failed: {err}\r\n");
+                    renderer.invalidate();
+                }
+            }
+            actions.extend(keys.iter().filter_map(|key| bindings.action(key)));
+            let actions = auto_repeat.apply(&actions, dt, state.gravity_interval());
+
+            // restart once the top out is shown, or right away on any key
+            if let Some(at) = over_at
+                && (!keys.is_empty() || !appearance.animations || state.clock() - at >= TOP_OUT)
+            {
+                over_at = None;
+                state.restart();
+                remember(&state);
+            }
+            let events = state.step(&actions, dt);
+            // frames without events only advance the timers, not worth a copy of the game
+            if !events.is_empty() {
+                remember(&state);
+            }
+            for event in events {
+                if event == Event::GameOver {
+                    over_at = Some(state.clock());
+                }
+                renderer.notify(&event);
+            }
+
+            match &rebinding {
+                Some(screen) => renderer.draw_menu(&screen.lines()),
+                None if state.is_paused() => renderer.draw_menu(&bindings::pause_screen(&bindings)),
+                None => renderer.draw_frame(&state),
+            }?;
+        }
+        Ok(())
+    };
+    let played = play();
+
+    // leave the terminal usable even when playing failed, so the error can be read
+    let finished = renderer.finish();
+    if let Some(path) = arg("--record=") {
+        std::fs::write(&path, input.file()).map_err(|err| Error::file(&path, err))?;
+    }
+    // leaves raw mode
+    drop(input);
+    played?;
+    finished?;
+    println!("You quit with {} points.", state.score().points());
+    Ok(())
+}
+
+// on a panic, saves a crash report, draws a crash screen and leaves the terminal usable before
+// printing the report
+fn install_crash_hook(appearance: Appearance) {
+    std::panic::set_hook(Box::new(move |info| {
+        let state = LAST_STATE.try_lock().ok().and_then(|state| state.clone());
+        let message = info.to_string();
+        let report = crash::report(&message, state.as_ref());
+        let path = crash::file_name(state.as_ref());
+        let saved = std::fs::write(&path, &report).is_ok();
+        let lines = crash::screen(&message, state.as_ref(), saved.then_some(&path));
+        if let Ok(mut renderer) = renderer_from_args() {
+            renderer.set_appearance(appearance);
+            let _ = renderer.draw_menu(&lines).and_then(|_| renderer.finish());
+        }
+        if let Some(original) = ORIGINAL_MODE.get() {
+            unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, original) };
+        }
+        eprint!("{report}");
+    }));
+}
+
+// keeps `state` for the report of a later crash
+fn remember(state: &GameState) {
+    *LAST_STATE.lock().unwrap_or_else(PoisonError::into_inner) = Some(state.clone());
+}
+
+fn renderer_from_args() -> Result<Box<dyn Renderer>, Error> {
+    match arg("--renderer=").as_deref() {
+        None | Some("terminal") => Ok(Box::new(AnsiRenderer::new(Terminal))),
+        Some("memory") => Ok(Box::new(PixelRenderer::new(MemoryBuffer::new(MEMORY_SIZE)))),
+        Some(other) => Err(Error::Setting(format!(
+            "unknown renderer {other}, expected terminal or memory"
+        ))),
+    }
+}
+
+fn appearance_from_args() -> Result<Appearance, Error> {
+    let block_style = match arg("--style=") {
+        None => BlockStyle::default(),
+        Some(name) => BlockStyle::named(&name).ok_or_else(|| {
+            Error::Setting(format!(
+                "unknown style {name}, expected flat, beveled, outlined or retro"
+            ))
+        })?,
+    };
+    Ok(Appearance {
+        theme: theme_from_args()?,
+        patterns: flag("--patterns"),
+        block_style,
+        grid: flag("--grid"),
+        animations: !flag("--no-animations"),
+    })
+}
+
+fn screenshot_format_from_args() -> Result<Format, Error> {
+    match arg("--screenshot=") {
+        None => Ok(Format::default()),
+        Some(name) => Format::named(&name).ok_or_else(|| {
+            Error::Setting(format!(
+                "unknown screenshot format {name}, expected ppm or bmp"
+            ))
+        }),
+    }
+}
+
+fn handling_from_args() -> Result<Handling, Error> {
+    let mut handling = Handling::default();
+    for (prefix, setting) in [
+        ("--das=", &mut handling.das),
+        ("--arr=", &mut handling.arr),
+        ("--sdf=", &mut handling.soft_drop_factor),
+    ] {
+        if let Some(value) = arg(prefix) {
+            *setting = value
+                .parse()
+                .map_err(|_| Error::Setting(format!("{prefix}{value} is no whole number")))?;
+        }
+    }
+    Ok(handling)
+}
+
+fn bindings_from_file(path: &str) -> Result<Bindings, Error> {
+    let file = match std::fs::read_to_string(path) {
+        Ok(file) => file,
+        // created when the keys are changed
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Bindings::default()),
+        Err(err) => return Err(Error::file(path, err)),
+    };
+    Bindings::parse(&file).map_err(|source| Error::Bindings {
+        path: path.into(),
+        source,
+    })
+}
+
+fn replay_from_file(path: &str) -> Result<Replay, Error> {
+    let file = std::fs::read_to_string(path).map_err(|err| Error::file(path, err))?;
+    Replay::parse(&file).map_err(|source| Error::Replay {
+        path: path.into(),
+        source,
+    })
+}
+
+fn theme_from_args() -> Result<Theme, Error> {
+    let Some(theme) = arg("--theme=") else {
+        return Ok(Theme::default());
+    };
+    if let Some(built_in) = Theme::named(&theme) {
+        return Ok(built_in);
+    }
+    let file = std::fs::read_to_string(&theme).map_err(|err| Error::file(&theme, err))?;
+    Theme::parse(&file).map_err(|source| Error::Theme {
+        path: theme,
+        source,
+    })
+}
+
+fn flag(name: &str) -> bool {
+    std::env::args().skip(1).any(|arg| arg == name)
+}
+
+// the value of the first argument starting with `prefix`
+fn arg(prefix: &str) -> Option<String> {
+    std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.strip_prefix(prefix).map(String::from))
+}
+
+// hands every written frame straight to stdout
+struct Terminal;
+
+impl fmt::Write for Terminal {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut stdout = io::stdout().lock();
+        stdout
+            .write_all(s.as_bytes())
+            .and_then(|_| stdout.flush())
+            .map_err(|_| fmt::Error)
+    }
+}
+
+fn seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_nanos() as u64)
+        .unwrap_or(42)
+}
+
+// the keys typed into the terminal, which is in raw mode until this is dropped
+struct TerminalInput {
+    _raw: RawMode,
+    buf: [u8; 256],
+    decoder: Decoder,
+    start: Instant,
+    pacer: Pacer,
+}
+
+impl TerminalInput {
+    fn new() -> io::Result<Self> {
+        Ok(Self {
+            _raw: RawMode::enable()?,
+            buf: [0; 256],
+            decoder: Decoder::default(),
+            start: Instant::now(),
+            pacer: Pacer::new(FRAME_TIME, 0),
+        })
+    }
+
+    // milliseconds since the input was opened
+    fn now(&self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+}
+
+impl InputSource for TerminalInput {
+    fn next_frame(&mut self) -> Result<Option<Frame>, InputError> {
+        // collect keys until the frame is over, so frames are equally long however fast keys come
+        let mut keys = Vec::new();
+        loop {
+            let now = self.now();
+            if self.pacer.is_due(now) {
+                let dt = self.pacer.finish(now);
+                return Ok(Some(Frame { keys, dt }));
+            }
+            let timeout = self.pacer.remaining(now);
+            keys.extend(
+                read_keys(&mut self.buf, &mut self.decoder, timeout)
+                    .map_err(|err| InputError::Read(err.to_string()))?,
+            );
+        }
+    }
+}
+
+// plays a replay back as fast as it was recorded
+struct RealTime(Replay);
+
+impl InputSource for RealTime {
+    fn next_frame(&mut self) -> Result<Option<Frame>, InputError> {
+        let frame = self.0.next_frame()?;
+        if let Some(frame) = &frame {
+            thread::sleep(Duration::from_millis(frame.dt.into()));
+        }
+        Ok(frame)
+    }
+}
+
+// waits at most `timeout` milliseconds for input and decodes every key it completes
+fn read_keys(buf: &mut [u8], decoder: &mut Decoder, timeout: u32) -> io::Result<Vec<Key>> {
+    let mut fd = libc::pollfd {
+        fd: libc::STDIN_FILENO,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let ready = unsafe { libc::poll(&mut fd, 1, timeout as i32) };
+    if ready < 0 {
+        let err = io::Error::last_os_error();
+        return if err.kind() == io::ErrorKind::Interrupted {
+            Ok(Vec::new())
+        } else {
+            Err(err)
+        };
+    }
+    if ready == 0 {
+        // nothing arrived in time, a pending escape byte may be the escape key
+        return Ok(decoder.wait(timeout).into_iter().collect());
+    }
+    // straight from the descriptor, input left in std's buffer would not wake up `poll`
+    let read = unsafe { libc::read(libc::STDIN_FILENO, buf.as_mut_ptr().cast(), buf.len()) };
+    if read < 0 {
+        let err = io::Error::last_os_error();
+        return if err.kind() == io::ErrorKind::Interrupted {
+            Ok(Vec::new())
+        } else {
+            Err(err)
+        };
+    }
+    Ok(decoder.decode(&buf[..read as usize]))
+}
+
+// puts the terminal into raw mode and restores the previous mode when dropped
+struct RawMode {
+    original: libc::termios,
+}
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        let mut original = unsafe { core::mem::zeroed::<libc::termios>() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut original) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        ORIGINAL_MODE.get_or_init(|| original);
+        let mut raw = original;
+        unsafe { libc::cfmakeraw(&mut raw) };
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { original })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original) };
+    }
+}
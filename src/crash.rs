@@ -0,0 +1,133 @@
+//! Reports about a crash, for frontends to show and save when they panic.
+//!
+//! A report holds the panic message, the seed and the last known [`GameState`] with its board
+//! drawn as text, which is enough to reproduce a crash by starting a game with the same seed and
+//! playing it up to that state:
+//!
+//! ```text
+//! tinyTetris crashed: panicked at src/game/mod.rs:10:5: oops
+//! seed: 42
+//! clock: 1600 ms
+//! ...
+//! |....tt....|
+//! |.....t....|
+//! |ZZ........|
+//! +----------+
+//! ```
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::Write;
+
+use crate::{COLS, GameState, LINES, game::piece::Cell, render::ansi::name_of};
+
+/// The full report of a crash of the game in `state`, `None` if no game was running yet.
+pub fn report(message: &str, state: Option<&GameState>) -> String {
+    let mut report = format!("tinyTetris crashed: {message}\n");
+    let Some(state) = state else {
+        report.push_str("no game was running\n");
+        return report;
+    };
+    let score = state.score();
+    let held = state.held_piece().map_or("none", name_of);
+    // writing to a String never fails
+    let _ = write!(
+        report,
+        "seed: {}\nclock: {} ms\nscore: {} points, {} lines, level {}\n\
+         falling: {}, held: {held}, next: {}\npaused: {}, over: {}\n\n{}",
+        state.randomizer().seed(),
+        state.clock(),
+        score.points(),
+        score.lines(),
+        score.level(),
+        name_of(state.falling_piece().kind()),
+        name_of(state.next_kind()),
+        state.is_paused(),
+        state.is_over(),
+        board(state),
+    );
+    report
+}
+
+/// The board of `state` as text, settled blocks as the letter of their kind and the falling piece
+/// in lowercase.
+pub fn board(state: &GameState) -> String {
+    let falling = state.falling_piece();
+    let mut board = String::with_capacity((LINES + 1) * (COLS + 3));
+    for (row, line) in state.board().rows().iter().enumerate() {
+        board.push('|');
+        for (col, block) in line.iter().enumerate() {
+            let cell = Cell::new(col as i32, row as i32);
+            let c = if falling.cells().contains(&cell) && !state.is_over() {
+                name_of(falling.kind()).to_lowercase()
+            } else {
+                block.map_or(".", name_of).to_string()
+            };
+            board.push_str(&c);
+        }
+        board.push_str("|\n");
+    }
+    board.push('+');
+    board.extend(core::iter::repeat_n('-', COLS));
+    board.push_str("+\n");
+    board
+}
+
+/// The lines of a crash screen, to be drawn with
+/// [`Renderer::draw_menu`](crate::Renderer::draw_menu). `dump` is where the report was saved, if
+/// it could be.
+pub fn screen(message: &str, state: Option<&GameState>, dump: Option<&str>) -> Vec<String> {
+    let mut lines = vec!["tinyTetris crashed".to_string()];
+    lines.extend(message.lines().map(String::from));
+    if let Some(state) = state {
+        lines.push(format!("seed {}", state.randomizer().seed()));
+    }
+    lines.push(match dump {
+        Some(path) => format!("report saved to {path}"),
+        None => "saving the report failed".to_string(),
+    });
+    lines
+}
+
+/// The name of the file the report of a crash of the game in `state` is saved to.
+pub fn file_name(state: Option<&GameState>) -> String {
+    format!("tinytetris-crash-{}.txt", state.map_or(0, GameState::clock))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Action;
+
+    #[test]
+    fn report_holds_the_seed_and_board() {
+        let mut state = GameState::new(42);
+        state.step(&[Action::HardDrop], 0);
+        let report = report("panicked at here: oops", Some(&state));
+        assert!(report.starts_with("tinyTetris crashed: panicked at here: oops\nseed: 42\n"));
+        let board = board(&state);
+        assert_eq!(board.lines().count(), LINES + 1);
+        assert!(board.lines().all(|line| line.chars().count() == COLS + 2));
+        // the dropped piece settled and the next one is falling
+        assert_eq!(board.matches(char::is_uppercase).count(), 4);
+        assert_eq!(board.matches(char::is_lowercase).count(), 4);
+        assert!(report.ends_with(&board));
+    }
+
+    #[test]
+    fn reports_without_a_game() {
+        assert_eq!(
+            report("oops", None),
+            "tinyTetris crashed: oops\nno game was running\n"
+        );
+        assert_eq!(file_name(None), "tinytetris-crash-0.txt");
+        assert_eq!(
+            screen("oops", None, None),
+            ["tinyTetris crashed", "oops", "saving the report failed"]
+        );
+    }
+}
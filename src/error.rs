@@ -0,0 +1,102 @@
+//! One error type for everything a frontend can run into, so failures can be propagated up to
+//! `main` and reported there.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::fmt::Display;
+
+use crate::{
+    interface::{
+        bindings::BindingsError,
+        source::{InputError, ReplayError},
+    },
+    render::{RenderError, settings::SettingsError, theme::ThemeError},
+};
+
+/// Everything that can stop tinyTetris.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// Drawing a frame failed.
+    #[error(transparent)]
+    Render(#[from] RenderError),
+    /// Reading the input failed.
+    #[error(transparent)]
+    Input(#[from] InputError),
+    /// A file could not be read or written.
+    #[error("{path}: {reason}")]
+    File {
+        /// The path of the file.
+        path: String,
+        /// What the platform reported.
+        reason: String,
+    },
+    /// A key file is invalid.
+    #[error("{path}: {source}")]
+    Bindings {
+        /// The path of the key file.
+        path: String,
+        /// What is wrong with it.
+        source: BindingsError,
+    },
+    /// A theme file is invalid.
+    #[error("{path}: {source}")]
+    Theme {
+        /// The path of the theme file.
+        path: String,
+        /// What is wrong with it.
+        source: ThemeError,
+    },
+    /// A settings file is invalid.
+    #[error("{path}: {source}")]
+    Settings {
+        /// The path of the settings file.
+        path: String,
+        /// What is wrong with it.
+        source: SettingsError,
+    },
+    /// A replay file is invalid.
+    #[error("{path}: {source}")]
+    Replay {
+        /// The path of the replay file.
+        path: String,
+        /// What is wrong with it.
+        source: ReplayError,
+    },
+    /// A setting, e.g. a command line argument, has a value it does not take.
+    #[error("{0}")]
+    Setting(String),
+}
+
+impl Error {
+    /// Accessing the file at `path` failed for `reason`.
+    pub fn file(path: &str, reason: impl Display) -> Self {
+        Self::File {
+            path: path.to_string(),
+            reason: format!("{reason}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_name_the_file_and_line() {
+        let err = Error::Bindings {
+            path: "tinytetris.keys".into(),
+            source: BindingsError::UnknownAction(3),
+        };
+        assert_eq!(err.to_string(), "tinytetris.keys: line 3: unknown action");
+        assert_eq!(
+            Error::file("replay.txt", "not found").to_string(),
+            "replay.txt: not found"
+        );
+        assert_eq!(
+            Error::from(InputError::Read("Input/output error".into())).to_string(),
+            "reading the input failed: Input/output error"
+        );
+    }
+}
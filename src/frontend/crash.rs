@@ -0,0 +1,67 @@
+use alloc::{format, string::String};
+use core::panic::PanicInfo;
+use libtinyos::eprintln;
+use spin::Mutex;
+use tiny_tetris::{
+    GameState, crash,
+    render::{Appearance, settings::Backend},
+};
+
+use super::{files::write_file, renderer};
+use crate::graphics::graphics;
+
+// the state after the last event, what the crash report shows
+static LAST_STATE: Mutex<Option<GameState>> = Mutex::new(None);
+// what draws the crash screen and how it looks, the defaults until the settings are loaded
+static SCREEN: Mutex<Option<(Backend, Appearance)>> = Mutex::new(None);
+
+// shows a crash screen and saves a report on every panic
+pub fn install_crash_hook() {
+    libtinyos::panic::set_hook(on_panic);
+}
+
+// draws the crash screen with `backend` and `appearance`
+pub fn configure(backend: Backend, appearance: Appearance) {
+    *SCREEN.lock() = Some((backend, appearance));
+}
+
+// keeps `state` for the report of a later crash
+pub fn remember(state: &GameState) {
+    *LAST_STATE.lock() = Some(state.clone());
+}
+
+fn on_panic(info: &PanicInfo) {
+    // a panic while remembering leaves no state to report
+    let state = LAST_STATE.try_lock().and_then(|state| state.clone());
+    let message = format!("{info}");
+    let report = crash::report(&message, state.as_ref());
+    eprintln!("{report}");
+    let path = crash::file_name(state.as_ref());
+    let saved = write_file(&path, report.as_bytes()).is_ok();
+    if !saved {
+        eprintln!("saving {path} failed");
+    }
+    draw_crash_screen(&crash::screen(
+        &message,
+        state.as_ref(),
+        saved.then_some(&path),
+    ));
+}
+
+// replaces whatever the framebuffer showed when the game panicked
+fn draw_crash_screen(lines: &[String]) {
+    if let Ok(graphics) = graphics() {
+        graphics.recover();
+    }
+    let (backend, appearance) = SCREEN
+        .try_lock()
+        .and_then(|screen| *screen)
+        .unwrap_or_default();
+    let Ok(mut renderer) = renderer(backend) else {
+        return;
+    };
+    renderer.set_appearance(appearance);
+    if renderer.draw_menu(lines).is_err() {
+        eprintln!("drawing the crash screen failed");
+    }
+}
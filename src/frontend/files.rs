@@ -0,0 +1,68 @@
+use alloc::vec::Vec;
+use libtinyos::syscalls::{self, OpenOptions, SysErr};
+
+// reads the whole file at `path`
+pub fn read_file(path: &str) -> Result<Vec<u8>, SysErr> {
+    let fd = unsafe { syscalls::open(path.as_ptr(), path.len(), OpenOptions::READ) }?;
+    let mut data = Vec::new();
+    let mut chunk = [0; 512];
+    let read = loop {
+        match unsafe { syscalls::read(fd, chunk.as_mut_ptr(), chunk.len(), 0) } {
+            Ok(0) => break Ok(()),
+            Ok(read) => data.extend_from_slice(&chunk[..read as usize]),
+            Err(err) => break Err(err),
+        }
+    };
+    unsafe { syscalls::close(fd) }?;
+    read.map(|_| data)
+}
+
+// whether there is a file at `path`
+pub fn exists(path: &str) -> bool {
+    match unsafe { syscalls::open(path.as_ptr(), path.len(), OpenOptions::READ) } {
+        Ok(fd) => {
+            let _ = unsafe { syscalls::close(fd) };
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+// why writing a file failed
+#[derive(Debug)]
+pub enum WriteError {
+    Sys(SysErr),
+    // the file took no more bytes, e.g. on a full disk
+    Incomplete,
+}
+
+impl From<SysErr> for WriteError {
+    fn from(err: SysErr) -> Self {
+        Self::Sys(err)
+    }
+}
+
+// replaces the file at `path` with `data`, creating it if needed
+pub fn write_file(path: &str, data: &[u8]) -> Result<(), WriteError> {
+    let fd = unsafe {
+        syscalls::open(
+            path.as_ptr(),
+            path.len(),
+            // without truncating, a shorter file would keep the end of the old one
+            OpenOptions::CREATE | OpenOptions::WRITE | OpenOptions::TRUNCATE,
+        )
+    }?;
+    let mut rest = data;
+    let written = loop {
+        if rest.is_empty() {
+            break Ok(());
+        }
+        match unsafe { syscalls::write(fd, rest.as_ptr(), rest.len()) } {
+            Ok(0) => break Err(WriteError::Incomplete),
+            Ok(written) => rest = &rest[written as usize..],
+            Err(err) => break Err(err.into()),
+        }
+    };
+    unsafe { syscalls::close(fd) }?;
+    written
+}
@@ -0,0 +1,70 @@
+use alloc::{format, vec::Vec};
+use libtinyos::syscalls;
+use tiny_tetris::interface::{
+    Key,
+    decode::Decoder,
+    pacing::Pacer,
+    source::{Frame, InputError, InputSource},
+};
+
+// milliseconds per frame, the game advances this often whether or not keys are pressed
+const FRAME_TIME: u32 = 16;
+// room for a burst of fast typing, escape sequences take up to a few bytes per key
+const INPUT_BUFFER: usize = 256;
+
+// the keys typed on the tinyOS console
+pub struct Stdin {
+    buf: [u8; INPUT_BUFFER],
+    decoder: Decoder,
+    pacer: Pacer,
+}
+
+impl Stdin {
+    pub fn new() -> Result<Self, InputError> {
+        Ok(Self {
+            buf: [0; INPUT_BUFFER],
+            decoder: Decoder::default(),
+            pacer: Pacer::new(FRAME_TIME, now()?),
+        })
+    }
+
+    // waits at most `timeout` milliseconds for input and decodes every key it completes
+    fn read(&mut self, timeout: u32) -> Result<Vec<Key>, InputError> {
+        unsafe { syscalls::seek(syscalls::STDIN_FILENO, 0) }
+            .map_err(|err| InputError::Seek(format!("{err:?}")))?;
+        let read = unsafe {
+            syscalls::read(
+                syscalls::STDIN_FILENO,
+                self.buf.as_mut_ptr(),
+                self.buf.len(),
+                timeout.into(),
+            )
+        }
+        .map_err(|err| InputError::Read(format!("{err:?}")))?;
+        if read == 0 {
+            // the read timed out, a pending escape byte may be the escape key
+            return Ok(self.decoder.wait(timeout).into_iter().collect());
+        }
+        Ok(self.decoder.decode(&self.buf[..read as usize]))
+    }
+}
+
+impl InputSource for Stdin {
+    fn next_frame(&mut self) -> Result<Option<Frame>, InputError> {
+        // collect keys until the frame is over, a full buffer is simply read again
+        let mut keys = Vec::new();
+        loop {
+            let now = now()?;
+            if self.pacer.is_due(now) {
+                let dt = self.pacer.finish(now);
+                return Ok(Some(Frame { keys, dt }));
+            }
+            keys.extend(self.read(self.pacer.remaining(now))?);
+        }
+    }
+}
+
+// milliseconds since boot
+fn now() -> Result<u64, InputError> {
+    syscalls::time().map_err(|err| InputError::Clock(format!("{err:?}")))
+}
@@ -0,0 +1,193 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt::Write;
+use libtinyos::{eprintln, print, println};
+use tiny_tetris::{
+    Action, Error, Event, GameState, Renderer,
+    interface::{
+        REBIND, SCREENSHOT,
+        bindings::{self, Bindings, Rebinding},
+        handling::{AutoRepeat, Handling},
+        source::{Frame, InputSource},
+    },
+    render::{
+        animation::{LINE_CLEAR, TOP_OUT},
+        ansi::AnsiRenderer,
+        buffer::DoubleBuffer,
+        pixel::PixelRenderer,
+        screenshot::{self, Format},
+        settings::{Backend, Settings},
+        theme::Theme,
+    },
+};
+
+use crate::graphics::KernelTarget;
+pub use crash::install_crash_hook;
+use files::{exists, read_file, write_file};
+pub use input::Stdin;
+
+mod crash;
+mod files;
+mod input;
+
+const SEED: u64 = 42;
+// the optional settings file read at startup, see `tiny_tetris::render::settings`
+const SETTINGS_FILE: &str = "tinytetris.settings";
+const SCREENSHOT_FORMAT: Format = Format::Ppm;
+// the key file read at startup and written when the keys are changed
+const KEYS_FILE: &str = "tinytetris.keys";
+
+// a renderer of the kind `backend`
+fn renderer(backend: Backend) -> Result<Box<dyn Renderer>, Error> {
+    Ok(match backend {
+        Backend::Framebuffer => {
+            Box::new(PixelRenderer::new(DoubleBuffer::new(KernelTarget::new()?)))
+        }
+        Backend::Terminal => Box::new(AnsiRenderer::new(Console)),
+    })
+}
+
+// writes text frames to stdout
+struct Console;
+
+impl Write for Console {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        print!("{s}");
+        Ok(())
+    }
+}
+
+// plays until `input` ends or fails
+pub fn game_loop(mut input: impl InputSource) -> Result<(), Error> {
+    println!("starting up...");
+    let mut bindings = load_bindings()?;
+    let Settings {
+        backend,
+        appearance,
+        ..
+    } = load_settings()?;
+    crash::configure(backend, appearance);
+    let mut state = GameState::new(SEED);
+    if appearance.animations {
+        state.set_line_clear_delay(LINE_CLEAR);
+    }
+    crash::remember(&state);
+    let mut renderer = renderer(backend)?;
+    renderer.set_appearance(appearance);
+    renderer.draw_frame(&state)?;
+    // the game clock when the last game was lost
+    let mut over_at = None;
+    let mut rebinding: Option<Rebinding> = None;
+    let mut auto_repeat = AutoRepeat::new(Handling::default());
+    while let Some(Frame {
+        keys: mut next_keycodes,
+        dt,
+    }) = input.next_frame()?
+    {
+        let mut actions = Vec::new();
+        if let Some(screen) = &mut rebinding {
+            // the rebinding screen takes every key
+            next_keycodes.drain(..).for_each(|key| screen.press(key));
+        }
+        if let Some(done) = rebinding.take_if(|screen| screen.action().is_none()) {
+            bindings = done.into_bindings();
+            if write_file(KEYS_FILE, bindings.to_file().as_bytes()).is_err() {
+                eprintln!("saving {KEYS_FILE} failed");
+                renderer.invalidate();
+            }
+        }
+        if next_keycodes.contains(&REBIND) {
+            rebinding = Some(Rebinding::new(bindings.clone()));
+            next_keycodes.clear();
+            if !state.is_paused() {
+                actions.push(Action::Pause);
+            }
+        }
+        if next_keycodes.contains(&SCREENSHOT) {
+            save_screenshot(&mut *renderer);
+        }
+        // every key typed since the last frame, in order
+        actions.extend(next_keycodes.iter().filter_map(|key| bindings.action(key)));
+        let actions = auto_repeat.apply(&actions, dt, state.gravity_interval());
+
+        // restart once the top out is shown, or right away on any key
+        if let Some(at) = over_at
+            && (!next_keycodes.is_empty()
+                || !appearance.animations
+                || state.clock() - at >= TOP_OUT)
+        {
+            over_at = None;
+            println!("starting up...");
+            state.restart();
+            crash::remember(&state);
+            // the messages may have been printed over the board
+            renderer.invalidate();
+        }
+        let events = state.step(&actions, dt);
+        // frames without events only advance the timers, not worth a copy of the game
+        if !events.is_empty() {
+            crash::remember(&state);
+        }
+        for event in events {
+            if event == Event::GameOver {
+                eprintln!(
+                    "You lost the game with {} points. Restarting...",
+                    state.score().points()
+                );
+                over_at = Some(state.clock());
+            }
+            renderer.notify(&event);
+        }
+        match &rebinding {
+            Some(screen) => renderer.draw_menu(&screen.lines()),
+            None if state.is_paused() => renderer.draw_menu(&bindings::pause_screen(&bindings)),
+            None => renderer.draw_frame(&state),
+        }?;
+    }
+    Ok(())
+}
+
+// the keys from KEYS_FILE, the defaults if there is none
+fn load_bindings() -> Result<Bindings, Error> {
+    // created when the keys are changed
+    let Ok(file) = read_file(KEYS_FILE) else {
+        return Ok(Bindings::default());
+    };
+    let file = str::from_utf8(&file).map_err(|err| Error::file(KEYS_FILE, err))?;
+    Bindings::parse(file).map_err(|source| Error::Bindings {
+        path: KEYS_FILE.into(),
+        source,
+    })
+}
+
+// the settings from SETTINGS_FILE with their theme file read, the defaults if there is none
+fn load_settings() -> Result<Settings, Error> {
+    let Ok(file) = read_file(SETTINGS_FILE) else {
+        return Ok(Settings::default());
+    };
+    let file = str::from_utf8(&file).map_err(|err| Error::file(SETTINGS_FILE, err))?;
+    let mut settings = Settings::parse(file).map_err(|source| Error::Settings {
+        path: SETTINGS_FILE.into(),
+        source,
+    })?;
+    if let Some(path) = settings.theme_file.take() {
+        let file = read_file(&path).map_err(|err| Error::file(&path, format_args!("{err:?}")))?;
+        let file = str::from_utf8(&file).map_err(|err| Error::file(&path, err))?;
+        settings.appearance.theme =
+            Theme::parse(file).map_err(|source| Error::Theme { path, source })?;
+    }
+    Ok(settings)
+}
+
+// saves the frame drawn last into the working directory
+fn save_screenshot(renderer: &mut dyn Renderer) {
+    let Some(shot) = renderer.screenshot() else {
+        return;
+    };
+    let path = screenshot::file_name(shot.extension(SCREENSHOT_FORMAT), exists);
+    match write_file(&path, &shot.encode(SCREENSHOT_FORMAT)) {
+        Ok(_) => println!("saved {path}"),
+        Err(_) => eprintln!("saving {path} failed"),
+    }
+    // the message may have been printed over the frame
+    renderer.invalidate();
+}
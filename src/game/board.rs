@@ -0,0 +1,161 @@
+//! The grid of settled blocks.
+
+use alloc::vec::Vec;
+
+use super::{
+    COLS, LINES,
+    piece::{Cell, Piece, PieceKind},
+};
+
+/// [`LINES`] rows of [`COLS`] cells, each either empty or holding a block of a piece.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Board {
+    cells: [[Option<PieceKind>; COLS]; LINES],
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self {
+            cells: [[None; COLS]; LINES],
+        }
+    }
+}
+
+impl Board {
+    /// The block at `cell`, `None` for empty or out of bounds cells.
+    pub fn get(&self, cell: Cell) -> Option<PieceKind> {
+        if !Self::in_bounds(cell) {
+            return None;
+        }
+        self.cells[cell.row as usize][cell.col as usize]
+    }
+
+    /// All rows, from the top of the board to the bottom.
+    pub fn rows(&self) -> &[[Option<PieceKind>; COLS]; LINES] {
+        &self.cells
+    }
+
+    /// Whether `cell` lies on the board.
+    pub fn in_bounds(cell: Cell) -> bool {
+        (0..COLS as i32).contains(&cell.col) && (0..LINES as i32).contains(&cell.row)
+    }
+
+    /// Whether `cell` lies on the board and is empty.
+    pub fn is_free(&self, cell: Cell) -> bool {
+        Self::in_bounds(cell) && self.cells[cell.row as usize][cell.col as usize].is_none()
+    }
+
+    /// Whether every cell of `piece` is free.
+    pub fn fits(&self, piece: &Piece) -> bool {
+        piece.cells().iter().all(|cell| self.is_free(*cell))
+    }
+
+    pub(super) fn set(&mut self, cell: Cell, kind: Option<PieceKind>) {
+        if Self::in_bounds(cell) {
+            self.cells[cell.row as usize][cell.col as usize] = kind;
+        }
+    }
+
+    pub(super) fn place(&mut self, piece: &Piece) {
+        for cell in piece.cells() {
+            self.set(*cell, Some(piece.kind()));
+        }
+    }
+
+    /// The indices of all completed rows, sorted from highest line (0) to lowest line (LINES).
+    pub fn full_lines(&self) -> Vec<usize> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| row.iter().all(Option::is_some))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    pub(super) fn clear_lines(&mut self, lines: &[usize]) {
+        for &line in lines {
+            self.cells[line] = [None; COLS];
+        }
+
+        // every row drops by the amount of cleared lines below it
+        let mut drop_amounts = [0; LINES];
+        for &line in lines.iter() {
+            for d in drop_amounts.iter_mut().take(line) {
+                *d += 1;
+            }
+        }
+        for from in (0..LINES).rev() {
+            if drop_amounts[from] > 0 {
+                let to = from + drop_amounts[from];
+                self.cells[to] = self.cells[from];
+                self.cells[from] = [None; COLS];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill_row(board: &mut Board, row: usize, except: &[i32]) {
+        for col in 0..COLS as i32 {
+            if !except.contains(&col) {
+                board.set(Cell::new(col, row as i32), Some(PieceKind::Quad));
+            }
+        }
+    }
+
+    #[test]
+    fn full_lines_are_sorted_top_to_bottom() {
+        let mut board = Board::default();
+        fill_row(&mut board, 18, &[]);
+        fill_row(&mut board, 12, &[]);
+        fill_row(&mut board, 15, &[3]);
+        assert_eq!(board.full_lines(), [12, 18]);
+    }
+
+    #[test]
+    fn clearing_a_single_line_drops_rows_above() {
+        let mut board = Board::default();
+        fill_row(&mut board, LINES - 1, &[]);
+        board.set(Cell::new(4, LINES as i32 - 2), Some(PieceKind::T));
+
+        board.clear_lines(&[LINES - 1]);
+
+        assert_eq!(board.full_lines(), []);
+        assert_eq!(board.rows()[LINES - 1][4], Some(PieceKind::T));
+        assert_eq!(board.rows()[LINES - 2], [None; COLS]);
+    }
+
+    #[test]
+    fn clearing_non_contiguous_lines_drops_each_row_by_the_lines_below_it() {
+        let mut board = Board::default();
+        board.set(Cell::new(0, 15), Some(PieceKind::Long));
+        fill_row(&mut board, 16, &[]);
+        board.set(Cell::new(1, 17), Some(PieceKind::T));
+        fill_row(&mut board, 18, &[]);
+        board.set(Cell::new(2, 19), Some(PieceKind::Z));
+
+        board.clear_lines(&[16, 18]);
+
+        let mut expected = Board::default();
+        expected.set(Cell::new(0, 17), Some(PieceKind::Long));
+        expected.set(Cell::new(1, 18), Some(PieceKind::T));
+        expected.set(Cell::new(2, 19), Some(PieceKind::Z));
+        assert_eq!(board, expected);
+    }
+
+    #[test]
+    fn pieces_only_fit_inside_the_board_on_free_cells() {
+        let mut board = Board::default();
+        let piece = Piece::spawn(PieceKind::Quad);
+        assert!(board.fits(&piece));
+        assert!(!board.fits(&piece.shifted(0, -1)));
+        assert!(!board.fits(&piece.shifted(COLS as i32, 0)));
+        assert!(!board.fits(&piece.shifted(0, LINES as i32)));
+
+        board.set(piece.cells()[0], Some(PieceKind::L));
+        assert!(!board.fits(&piece));
+    }
+}
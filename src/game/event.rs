@@ -0,0 +1,162 @@
+//! What happens during a game, for everyone who wants to react to it.
+
+use alloc::vec::Vec;
+
+use super::piece::{Piece, PieceKind};
+
+/// Something that happened during a [`GameState::step`](super::GameState::step).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A new piece entered the board at its spawn position.
+    PieceSpawned(Piece),
+    /// The falling piece was moved or rotated.
+    PieceMoved {
+        /// The piece before the move.
+        from: Piece,
+        /// The piece after the move.
+        to: Piece,
+    },
+    /// The falling piece became part of the board.
+    PieceLocked(Piece),
+    /// Completed lines were removed from the board.
+    LinesCleared {
+        /// The cleared rows, sorted from highest line (0) to lowest line (LINES).
+        lines: Vec<usize>,
+        /// How many lines were cleared at once.
+        kind: ClearKind,
+    },
+    /// The game reached the given level.
+    LevelUp(u32),
+    /// The falling piece of the given kind was put on hold.
+    Hold(PieceKind),
+    /// The game was paused with [`Action::Pause`](super::Action::Pause).
+    Paused,
+    /// The paused game was resumed.
+    Resumed,
+    /// The next piece could not spawn. The game stays over until
+    /// [`GameState::restart`](super::GameState::restart).
+    GameOver,
+}
+
+/// The number of lines cleared by a single piece.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClearKind {
+    /// One line.
+    Single,
+    /// Two lines.
+    Double,
+    /// Three lines.
+    Triple,
+    /// Four lines, only possible with a [`PieceKind::Long`].
+    Tetris,
+}
+
+impl ClearKind {
+    /// Every kind, from the fewest to the most lines.
+    pub const ALL: [ClearKind; 4] = [
+        ClearKind::Single,
+        ClearKind::Double,
+        ClearKind::Triple,
+        ClearKind::Tetris,
+    ];
+
+    /// The kind of clearing `lines` lines at once, `None` if no piece can clear that many.
+    pub fn from_lines(lines: usize) -> Option<Self> {
+        Self::ALL.get(lines.checked_sub(1)?).copied()
+    }
+
+    /// The number of lines cleared.
+    pub fn lines(self) -> usize {
+        self as usize + 1
+    }
+}
+
+/// Anything that wants to be told about [`Event`]s, e.g. renderers, statistics or replays.
+pub trait Subscriber {
+    /// Called once per event, in the order the events happened.
+    fn notify(&mut self, event: &Event);
+}
+
+impl<F> Subscriber for F
+where
+    F: FnMut(&Event),
+{
+    fn notify(&mut self, event: &Event) {
+        self(event)
+    }
+}
+
+/// Hands every event to every subscriber, in order.
+pub fn publish(events: &[Event], subscribers: &mut [&mut dyn Subscriber]) {
+    for event in events {
+        for subscriber in subscribers.iter_mut() {
+            subscriber.notify(event);
+        }
+    }
+}
+
+/// Counts spawned pieces and line clears, as an example of a [`Subscriber`] living outside of
+/// the game rules.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Statistics {
+    pieces: [u32; PieceKind::ALL.len()],
+    clears: [u32; ClearKind::ALL.len()],
+}
+
+impl Statistics {
+    /// How many pieces of `kind` were spawned.
+    pub fn pieces(&self, kind: PieceKind) -> u32 {
+        self.pieces[kind as usize]
+    }
+
+    /// How often `kind` was cleared.
+    pub fn clears(&self, kind: ClearKind) -> u32 {
+        self.clears[kind as usize]
+    }
+}
+
+impl Subscriber for Statistics {
+    fn notify(&mut self, event: &Event) {
+        match event {
+            Event::PieceSpawned(piece) => self.pieces[piece.kind() as usize] += 1,
+            Event::LinesCleared { kind, .. } => self.clears[*kind as usize] += 1,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clear_kinds_match_their_line_count() {
+        assert_eq!(ClearKind::from_lines(0), None);
+        assert_eq!(ClearKind::from_lines(5), None);
+        for kind in ClearKind::ALL {
+            assert_eq!(ClearKind::from_lines(kind.lines()), Some(kind));
+        }
+    }
+
+    #[test]
+    fn every_subscriber_sees_every_event_in_order() {
+        let events = [
+            Event::PieceSpawned(Piece::spawn(PieceKind::T)),
+            Event::LinesCleared {
+                lines: alloc::vec![3, 4],
+                kind: ClearKind::Double,
+            },
+            Event::GameOver,
+        ];
+        let mut statistics = Statistics::default();
+        let mut seen = Vec::new();
+        let mut record = |event: &Event| seen.push(event.clone());
+
+        publish(&events, &mut [&mut statistics, &mut record]);
+
+        assert_eq!(seen, events);
+        assert_eq!(statistics.pieces(PieceKind::T), 1);
+        assert_eq!(statistics.pieces(PieceKind::L), 0);
+        assert_eq!(statistics.clears(ClearKind::Double), 1);
+    }
+}
@@ -1,748 +1,870 @@
-use alloc::vec;
+//! The game rules: the [`GameState`] of a running game and everything it is made of.
+
 use alloc::vec::Vec;
-use conquer_once::spin::OnceCell;
-use libtinyos::{eprintln, println};
-use rand::{Rng, SeedableRng, rngs::SmallRng};
-use spin::Mutex;
-use tinygraphics::{
-    backend::GraphicsBackend,
-    pixelcolor::Rgb888,
-    prelude::{Dimensions, Point, Primitive, RgbColor, Size},
-    primitives::{PrimitiveStyle, PrimitiveStyleBuilder, Rectangle, StrokeAlignment},
-};
-
-use crate::{
-    graphics::graphics,
-    interface::{KeyCode, query_keyboard_once},
-};
-
-// TODO: clean up blocking checks, ...
-
-const X_ANCHOR: i32 = 300;
-const Y_ANCHOR: i32 = 100;
-const MAX_X: i32 = 500;
-const MAX_Y: i32 = 300;
-
-const GRANULE_SIZE: i32 = 10;
-const LINES: usize = ((MAX_Y - Y_ANCHOR) / GRANULE_SIZE) as usize;
-const COLS: usize = ((MAX_X - X_ANCHOR) / GRANULE_SIZE) as usize;
+
+use board::Board;
+use event::{ClearKind, Event, Subscriber};
+use piece::{Piece, PieceKind, Rotation};
+use randomizer::{Randomizer, RandomizerKind};
+use scoring::Score;
+
+pub mod board;
+pub mod event;
+pub mod piece;
+pub mod randomizer;
+pub mod scoring;
+
+/// Width of the board in cells.
+pub const COLS: usize = 20;
+/// Height of the board in cells.
+pub const LINES: usize = 20;
+
+// time in ms it takes the falling piece to drop by one line on its own at level 0
+const GRAVITY_INTERVAL: u32 = 500;
+// every level drops the falling piece this much faster, down to MIN_GRAVITY_INTERVAL
+const GRAVITY_SPEEDUP: u32 = 40;
+const MIN_GRAVITY_INTERVAL: u32 = 50;
+
+// offsets (cols, rows) tried in order when a rotation is blocked in place
+const KICKS: [(i32, i32); 6] = [(0, 0), (-1, 0), (1, 0), (0, -1), (-2, 0), (2, 0)];
 
 /*
 coord system:
-             MAX_X, col_idx == COLS
-             |
-[            |
-    0, 0, 0, 0 <--- Y_ANCHOR, row_idx == 0
+                col_idx == COLS
+                |
+[               |
+    0, 0, 0, 0 <--- row_idx == 0
     0, 0, 0, 0
-    0, 0, 0, 0 <-- MAX_Y, row_idx == LINES
+    0, 0, 0, 0 <-- row_idx == LINES
 ]   |
     |
-    X_ANCHOR
     col_idx == 0
 */
 
-static RNG: OnceCell<Mutex<SmallRng>> = OnceCell::uninit();
-
-pub fn game_loop() {
-    let mut buf: [u8; 10] = [0; 10];
-    let mut state = GameState::new();
-    loop {
-        // currently this blocks. TODO: add block with timeout, such that the game progresses without input
-        let next_keycodes = query_keyboard_once(&mut buf);
-        state.handle_input(next_keycodes.first());
-        state.validate();
-    }
+/// An input of the player, applied to the falling piece.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Move one column to the left.
+    Left,
+    /// Move one column to the right.
+    Right,
+    /// Move one line down, locking the piece if it already rests on something.
+    SoftDrop,
+    /// Rotate clockwise, kicking the piece aside if needed.
+    RotateClockwise,
+    /// Rotate counterclockwise, kicking the piece aside if needed.
+    RotateCounterclockwise,
+    /// Rotate by half a turn, kicking the piece aside if needed.
+    Rotate180,
+    /// Drop the piece as far as it goes and lock it right away.
+    HardDrop,
+    /// Swap the falling piece with the held one. Allowed once per piece.
+    Hold,
+    /// Stop or resume the game. While paused, time stands still and all other actions are
+    /// ignored.
+    Pause,
 }
 
+/// The game rules without any I/O. A frontend feeds in actions and elapsed time and draws the
+/// resulting state.
 #[derive(Debug, PartialEq, Eq, Clone)]
-struct GameState {
-    score: u32,
-    line_counts: [u8; LINES],
-    heights: [i32; COLS],
-    settled_piece: Shape,
-    falling_piece: Shape,
+pub struct GameState {
+    board: Board,
+    falling_piece: Piece,
+    held_piece: Option<PieceKind>,
+    can_hold: bool,
+    randomizer: Randomizer,
+    score: Score,
+    gravity_timer: u32,
+    line_clear_delay: u32,
+    clearing: Option<Clearing>,
+    elapsed: u32,
+    clock: u32,
+    paused: bool,
+    game_over: bool,
+    events: Vec<Event>,
 }
 
-impl GameState {
-    fn new() -> Self {
-        println!("starting up...");
+/// Completed lines waiting for the line-clear delay to pass before they are removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clearing {
+    lines: Vec<usize>,
+    elapsed: u32,
+}
+
+impl Clearing {
+    /// The completed rows, sorted from highest line (0) to lowest line (LINES).
+    pub fn lines(&self) -> &[usize] {
+        &self.lines
+    }
 
-        Self::redraw();
-        let first = ShapeBuilder::long().build();
-        first.draw();
+    /// Milliseconds since the lines were completed.
+    pub fn elapsed(&self) -> u32 {
+        self.elapsed
+    }
+}
 
-        // graphics().inner().flush().unwrap();
+impl GameState {
+    /// Starts a game dealing pieces from the default randomizer seeded with `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self::with_randomizer(Randomizer::new(RandomizerKind::default(), seed))
+    }
 
+    /// Starts a game dealing pieces from `randomizer`. The first piece is announced by the first
+    /// [`step`](Self::step).
+    pub fn with_randomizer(mut randomizer: Randomizer) -> Self {
+        let first = Piece::spawn(randomizer.next_kind());
         Self {
-            score: 0,
-            line_counts: [0; LINES],
-            heights: [0; COLS],
-            settled_piece: Shape::default(),
-            falling_piece: first,
+            board: Board::default(),
+            falling_piece: first.clone(),
+            held_piece: None,
+            can_hold: true,
+            randomizer,
+            score: Score::default(),
+            gravity_timer: 0,
+            line_clear_delay: 0,
+            clearing: None,
+            elapsed: 0,
+            clock: 0,
+            paused: false,
+            game_over: false,
+            events: alloc::vec![Event::PieceSpawned(first)],
         }
     }
 
-    fn next_piece(&mut self) {
-        self.falling_piece = match RNG
-            .get_or_init(|| Mutex::new(SmallRng::seed_from_u64(42)))
-            .lock()
-            .random_range(..5)
-        {
-            0_u32 => ShapeBuilder::long(),
-            1_u32 => ShapeBuilder::quad(),
-            2_u32 => ShapeBuilder::t(),
-            3_u32 => ShapeBuilder::z(),
-            4_u32 => ShapeBuilder::l(),
-            _ => unreachable!(),
-        }
-        .build();
-    }
-
-    fn handle_input(&mut self, input: Option<&KeyCode>) {
-        _ = match input {
-            Some(KeyCode::ArrowDown) => self.falling_piece.down(),
-            Some(KeyCode::ArrowLeft) => self.falling_piece.left_checked(|shape| {
-                shape
-                    .elements
-                    .iter()
-                    .any(|element| would_be_blocked(element, &self.heights))
-            }),
-            Some(KeyCode::ArrowRight) => self.falling_piece.right_checked(|shape| {
-                shape
-                    .elements
-                    .iter()
-                    .any(|element| would_be_blocked(element, &self.heights))
-            }),
-            Some(KeyCode::Esc) => {
-                // TODO menu
-                None
-            }
-            Some(KeyCode::Char('k')) => self
-                .falling_piece
-                .rotate_counterclockwise(|element| would_be_blocked(element, &self.heights)),
-            Some(KeyCode::Char('l')) => self
-                .falling_piece
-                .rotate_clockwise(|element| would_be_blocked(element, &self.heights)),
-            None => self.falling_piece.down(),
-            _ => None,
-        };
+    /// The settled blocks.
+    pub fn board(&self) -> &Board {
+        &self.board
     }
 
-    fn redraw() {
-        graphics()
-            .inner()
-            .draw_primitive(
-                &Rectangle::new(
-                    Point::new(X_ANCHOR, Y_ANCHOR),
-                    Size::new((MAX_X - X_ANCHOR) as u32, (MAX_Y - Y_ANCHOR) as u32),
-                )
-                .into_styled(
-                    PrimitiveStyleBuilder::new()
-                        .fill_color(Rgb888::BLACK)
-                        .stroke_color(Rgb888::WHITE)
-                        .stroke_alignment(StrokeAlignment::Outside)
-                        .stroke_width(4)
-                        .build(),
-                ),
-            )
-            .unwrap();
-    }
-
-    fn validate(&mut self) {
-        // No need to flush currently, as we use the kernel fb via mmap
-        Self::redraw();
-        if self
-            .falling_piece
-            .elements
-            .iter()
-            .any(|element| is_blocked(element, &self.heights))
-        {
-            self.handle_collision();
-            self.next_piece();
-        }
-        self.settled_piece.draw();
-        self.falling_piece.draw();
+    /// The piece currently controlled by the player.
+    pub fn falling_piece(&self) -> &Piece {
+        &self.falling_piece
+    }
 
-        // graphics().inner().flush().unwrap();
+    /// Where the falling piece would lock if it kept falling.
+    pub fn ghost_piece(&self) -> Piece {
+        let mut ghost = self.falling_piece.clone();
+        loop {
+            let below = ghost.shifted(0, 1);
+            if !self.board.fits(&below) {
+                return ghost;
+            }
+            ghost = below;
+        }
     }
 
-    fn handle_collision(&mut self) {
-        let mut full = Vec::new();
-        for element in self.falling_piece.elements.iter() {
-            let row = ((element.top_left().y - Y_ANCHOR) / GRANULE_SIZE) as usize;
-            let col = ((element.top_left().x - X_ANCHOR) / GRANULE_SIZE) as usize;
+    /// The kind of the piece spawning after the falling one.
+    pub fn next_kind(&self) -> PieceKind {
+        // randomizers are deterministic, so a copy deals the same piece
+        self.randomizer.clone().next_kind()
+    }
 
-            if row <= 1 {
-                self.restart();
-                return;
-            }
+    /// The piece put on hold, if any.
+    pub fn held_piece(&self) -> Option<PieceKind> {
+        self.held_piece
+    }
 
-            self.heights[col] = LINES as i32 - row as i32;
-            self.line_counts[row] += 1;
-            if self.line_counts[row] >= COLS as u8 {
-                full.push(row);
-                self.line_counts[row] = 0;
-            }
-        }
+    /// Points, lines and level of the current game.
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
 
-        self.settled_piece.merge(self.falling_piece.clone());
-        if !full.is_empty() {
-            // sort cleared lines, such that higher lines (lower idx) get popped first
-            full.sort();
-            self.clear_lines(&full);
-        }
+    /// The randomizer dealing the pieces.
+    pub fn randomizer(&self) -> &Randomizer {
+        &self.randomizer
     }
 
-    fn clear_lines(&mut self, lines: &[usize]) {
-        // lines sorted form highest line (0) to lowest line (LINES)
-        self.settled_piece.remove(|element| {
-            lines.contains(&(((element.top_left().y - Y_ANCHOR) / GRANULE_SIZE) as usize))
-        });
-
-        let mut acc = Vec::with_capacity(lines.len());
-
-        // drop lines down from highest to lowest line
-        for (i, line) in lines.iter().enumerate() {
-            let Some(mut falling) = self
-                .settled_piece
-                .split_at_y(*line as i32 * GRANULE_SIZE + Y_ANCHOR)
-            else {
-                continue;
-            };
-            for _ in 0..(lines.len() - i) {
-                falling.down();
-            }
-            acc.push(falling);
-        }
+    /// Milliseconds played so far, time stops when the game is over.
+    pub fn elapsed(&self) -> u32 {
+        self.elapsed
+    }
 
-        for falling in acc {
-            self.settled_piece.merge(falling);
-        }
+    /// Milliseconds passed to [`step`](Self::step) since the first game, including time spent
+    /// after game over. Animations run on this clock.
+    pub fn clock(&self) -> u32 {
+        self.clock
+    }
 
-        let mut drop_amounts = [0; LINES];
-        for &line in lines.iter() {
-            for d in drop_amounts.iter_mut().take(line) {
-                *d += 1;
-            }
-        }
-        for from in (0..LINES).rev() {
-            if drop_amounts[from] > 0 {
-                let to = from + drop_amounts[from];
-                self.line_counts[to] = self.line_counts[from];
-                self.line_counts[from] = 0;
-            }
-        }
+    /// How long completed lines stay on the board before they are removed, in milliseconds.
+    pub fn line_clear_delay(&self) -> u32 {
+        self.line_clear_delay
+    }
 
-        self.calculate_heights();
-        self.score += (lines.len() * COLS) as u32;
-    }
-
-    fn calculate_heights(&mut self) {
-        for (col, height) in self.heights.iter_mut().enumerate() {
-            let x = col as i32 * GRANULE_SIZE + X_ANCHOR;
-            let top_most = self
-                .settled_piece
-                .extreme(
-                    |lhs, rhs| {
-                        rhs.top_left().x == x
-                            && (rhs.top_left().y < lhs.top_left().y || lhs.top_left().x != x)
-                    },
-                    |element| element.top_left().x == x && element.top_left().y == Y_ANCHOR,
-                )
-                .map(|item| {
-                    if item.top_left().x != x {
-                        LINES
-                    } else {
-                        ((item.top_left().y - Y_ANCHOR) / GRANULE_SIZE) as usize
-                    }
-                })
-                .unwrap_or(LINES);
-            *height = (LINES - top_most) as i32;
-        }
+    /// Keeps completed lines on the board for `delay` milliseconds, e.g. to animate them. Any
+    /// action skips the rest of the delay. Defaults to 0, clearing lines right away.
+    pub fn set_line_clear_delay(&mut self, delay: u32) {
+        self.line_clear_delay = delay;
     }
 
-    fn restart(&mut self) {
-        eprintln!(
-            "You lost the game with {} points. Restarting...",
-            self.score
-        );
-        *self = Self::new();
+    /// The completed lines waiting to be removed, if any. The falling piece is not in play while
+    /// lines are clearing.
+    pub fn clearing(&self) -> Option<&Clearing> {
+        self.clearing.as_ref()
     }
-}
 
-fn is_blocked(element: &PrimitiveBox, heights: &[i32]) -> bool {
-    element.top_left().y + element.inner.size.height as i32
-        >= MAX_Y
-            - (heights[((element.top_left().x - X_ANCHOR) / GRANULE_SIZE) as usize]) * GRANULE_SIZE
-}
+    /// Whether the game has topped out.
+    pub fn is_over(&self) -> bool {
+        self.game_over
+    }
 
-fn would_be_blocked(element: &PrimitiveBox, heights: &[i32]) -> bool {
-    element.top_left().y
-        >= MAX_Y
-            - (heights[((element.top_left().x - X_ANCHOR) / GRANULE_SIZE) as usize]) * GRANULE_SIZE
-}
+    /// Whether the game is paused, see [`Action::Pause`].
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
 
-fn snap_to_grid(point: &mut Point, scaler: i32) {
-    let dx = point.x % GRANULE_SIZE;
-    let dy = point.y % GRANULE_SIZE;
+    /// Milliseconds it takes the falling piece to drop by one line on its own at the current
+    /// level.
+    pub fn gravity_interval(&self) -> u32 {
+        GRAVITY_INTERVAL
+            .saturating_sub(self.score.level() * GRAVITY_SPEEDUP)
+            .max(MIN_GRAVITY_INTERVAL)
+    }
 
-    if dx < GRANULE_SIZE / scaler {
-        point.x -= dx;
-    } else {
-        point.x += GRANULE_SIZE - dx;
+    /// Advances the game by applying `actions` in order, then letting `dt` ms of gravity pass.
+    /// Returns everything that happened since the last step, in order.
+    pub fn step(&mut self, actions: &[Action], dt: u32) -> Vec<Event> {
+        self.clock += dt;
+        if !self.game_over {
+            let mut actions = actions;
+            if self.paused {
+                // everything up to resuming is ignored
+                let resume = actions.iter().position(|action| *action == Action::Pause);
+                actions = resume.map_or(&[], |idx| &actions[idx + 1..]);
+                if resume.is_some() {
+                    self.paused = false;
+                    self.emit(Event::Resumed);
+                }
+            }
+            if !self.paused {
+                self.elapsed += dt;
+                self.advance(actions, dt);
+            }
+        }
+        core::mem::take(&mut self.events)
     }
-    if dy < GRANULE_SIZE / scaler {
-        point.y -= dy;
-    } else {
-        point.y += GRANULE_SIZE - dy;
+
+    /// Starts a new game, the randomizer, the clock and the line-clear delay keep their state.
+    pub fn restart(&mut self) {
+        let restarted = Self {
+            line_clear_delay: self.line_clear_delay,
+            clock: self.clock,
+            ..Self::with_randomizer(self.randomizer.clone())
+        };
+        *self = restarted;
     }
-}
 
-trait Object {
-    fn left(&mut self) -> Option<()>;
-    fn right(&mut self) -> Option<()>;
-    fn down(&mut self) -> Option<()>;
-    fn draw(&self);
-    fn top_left(&self) -> Point;
-    fn bounding_box(&self) -> Rectangle;
-}
+    fn advance(&mut self, actions: &[Action], mut dt: u32) {
+        if let Some(clearing) = &mut self.clearing {
+            clearing.elapsed += dt;
+            if actions.is_empty() && clearing.elapsed < self.line_clear_delay {
+                return;
+            }
+            // the time left after the delay counts for the next piece
+            dt = clearing.elapsed.saturating_sub(self.line_clear_delay);
+            let full = core::mem::take(&mut clearing.lines);
+            self.clearing = None;
+            self.finish_lock(full);
+            if self.game_over {
+                return;
+            }
+        }
 
-#[derive(Debug, Default, PartialEq, Eq, Clone)]
-struct PrimitiveBox {
-    inner: Rectangle,
-    style: PrimitiveStyle<Rgb888>,
-}
+        for action in actions {
+            self.apply(*action);
+            if self.game_over || self.clearing.is_some() || self.paused {
+                return;
+            }
+        }
 
-impl PrimitiveBox {
-    fn new(x: i32, y: i32, color: Rgb888) -> Self {
-        Self {
-            inner: Rectangle {
-                top_left: Point { x, y },
-                size: Size::new(GRANULE_SIZE as u32, GRANULE_SIZE as u32),
-            },
-            style: PrimitiveStyleBuilder::new()
-                .fill_color(color)
-                .stroke_color(color)
-                .build(),
+        self.gravity_timer += dt;
+        while self.gravity_timer >= self.gravity_interval() {
+            self.gravity_timer -= self.gravity_interval();
+            self.fall();
+            if self.game_over || self.clearing.is_some() {
+                return;
+            }
         }
     }
 
-    fn is_in_bounds(&self) -> bool {
-        self.inner.top_left.x >= X_ANCHOR
-            && self.inner.top_left.x + self.inner.size.width as i32 <= MAX_X
-            && self.inner.top_left.y >= Y_ANCHOR
-            && self.inner.top_left.y + self.inner.size.height as i32 <= MAX_Y
+    fn emit(&mut self, event: Event) {
+        let level = self.score.level();
+        self.score.notify(&event);
+        self.events.push(event);
+        if self.score.level() > level {
+            self.events.push(Event::LevelUp(self.score.level()));
+        }
     }
-}
 
-impl Object for PrimitiveBox {
-    fn left(&mut self) -> Option<()> {
-        self.inner.top_left.x -= GRANULE_SIZE;
-        Some(())
+    fn apply(&mut self, action: Action) {
+        _ = match action {
+            Action::Left => self.try_move(-1, 0),
+            Action::Right => self.try_move(1, 0),
+            Action::SoftDrop => {
+                self.fall();
+                Some(())
+            }
+            Action::RotateClockwise => self.try_rotate(Rotation::Clockwise),
+            Action::RotateCounterclockwise => self.try_rotate(Rotation::Counterclockwise),
+            Action::Rotate180 => self.try_turn(
+                self.falling_piece
+                    .rotated(Rotation::Clockwise)
+                    .rotated(Rotation::Clockwise),
+            ),
+            Action::HardDrop => {
+                self.hard_drop();
+                Some(())
+            }
+            Action::Hold => self.hold(),
+            Action::Pause => {
+                self.paused = true;
+                self.emit(Event::Paused);
+                Some(())
+            }
+        };
     }
 
-    fn right(&mut self) -> Option<()> {
-        self.inner.top_left.x += GRANULE_SIZE;
+    fn try_move(&mut self, cols: i32, rows: i32) -> Option<()> {
+        let moved = self.falling_piece.shifted(cols, rows);
+        if !self.board.fits(&moved) {
+            return None;
+        }
+        self.move_to(moved);
         Some(())
     }
 
-    fn down(&mut self) -> Option<()> {
-        self.inner.top_left.y += GRANULE_SIZE;
-        Some(())
+    fn try_rotate(&mut self, rotation: Rotation) -> Option<()> {
+        self.try_turn(self.falling_piece.rotated(rotation))
     }
 
-    fn draw(&self) {
-        graphics()
-            .inner()
-            .draw_primitive(&self.inner.into_styled(self.style))
-            .unwrap();
+    // moves to `rotated` or the first kick of it that fits
+    fn try_turn(&mut self, rotated: Piece) -> Option<()> {
+        let kicked = KICKS
+            .iter()
+            .map(|(cols, rows)| rotated.shifted(*cols, *rows))
+            .find(|candidate| self.board.fits(candidate))?;
+        self.move_to(kicked);
+        Some(())
     }
 
-    fn top_left(&self) -> Point {
-        self.inner.top_left
+    fn move_to(&mut self, piece: Piece) {
+        let from = core::mem::replace(&mut self.falling_piece, piece.clone());
+        self.emit(Event::PieceMoved { from, to: piece });
     }
 
-    fn bounding_box(&self) -> Rectangle {
-        self.inner.bounding_box()
+    fn hold(&mut self) -> Option<()> {
+        if !self.can_hold {
+            return None;
+        }
+        let current = self.falling_piece.kind();
+        let next = match self.held_piece.replace(current) {
+            Some(held) => held,
+            None => self.randomizer.next_kind(),
+        };
+        self.emit(Event::Hold(current));
+        self.spawn(next);
+        // spawning resets this for regular pieces
+        self.can_hold = false;
+        Some(())
     }
-}
 
-#[derive(Default, Debug, PartialEq, Eq, Clone)]
-struct Shape {
-    elements: Vec<PrimitiveBox>,
-    pivot: Point,
-}
-
-impl Shape {
-    fn extreme<F, M>(&self, cmp: F, stop: M) -> Option<&PrimitiveBox>
-    where
-        F: Fn(&PrimitiveBox, &PrimitiveBox) -> bool,
-        M: Fn(&PrimitiveBox) -> bool,
-    {
-        let mut elements = self.elements.iter();
-        let mut current = elements.next()?;
-        for element in elements {
-            if stop(current) {
-                break;
-            }
-            if cmp(current, element) {
-                current = element;
-            }
+    fn hard_drop(&mut self) {
+        let ghost = self.ghost_piece();
+        if ghost != self.falling_piece {
+            self.move_to(ghost);
         }
-        Some(current)
+        self.handle_collision();
     }
 
-    fn leftmost(&self) -> Option<&PrimitiveBox> {
-        self.extreme(
-            |left, right| right.top_left().x < left.top_left().x,
-            |element| element.top_left().x == X_ANCHOR,
-        )
+    fn fall(&mut self) {
+        if self.try_move(0, 1).is_none() {
+            self.handle_collision();
+        }
     }
 
-    fn rightmost(&self) -> Option<&PrimitiveBox> {
-        self.extreme(
-            |left, right| right.top_left().x > left.top_left().x,
-            |element| element.top_left().x + element.bounding_box().size.width as i32 == MAX_X,
-        )
+    fn handle_collision(&mut self) {
+        self.board.place(&self.falling_piece);
+        self.emit(Event::PieceLocked(self.falling_piece.clone()));
+
+        let full = self.board.full_lines();
+        if !full.is_empty() && self.line_clear_delay > 0 {
+            self.clearing = Some(Clearing {
+                lines: full,
+                elapsed: 0,
+            });
+            return;
+        }
+        self.finish_lock(full);
     }
 
-    fn lowest(&self) -> Option<&PrimitiveBox> {
-        self.extreme(
-            |left, right| right.top_left().y > left.top_left().y,
-            |element| element.top_left().y + element.bounding_box().size.height as i32 == MAX_Y,
-        )
-    }
+    // removes the completed lines and spawns the next piece
+    fn finish_lock(&mut self, full: Vec<usize>) {
+        if !full.is_empty() {
+            self.clear_lines(full);
+        }
 
-    fn highest(&self) -> Option<&PrimitiveBox> {
-        self.extreme(
-            |lhs, rhs| rhs.top_left().y < lhs.top_left().y,
-            |element| element.top_left().y == Y_ANCHOR,
-        )
+        let next = self.randomizer.next_kind();
+        self.spawn(next);
     }
 
-    fn merge(&mut self, other: Shape) {
-        self.elements.extend(other.elements);
+    fn clear_lines(&mut self, lines: Vec<usize>) {
+        // lines sorted form highest line (0) to lowest line (LINES)
+        self.board.clear_lines(&lines);
+        if let Some(kind) = ClearKind::from_lines(lines.len()) {
+            self.emit(Event::LinesCleared { lines, kind });
+        }
     }
 
-    fn split_at_y(&mut self, y: i32) -> Option<Shape> {
-        // keeps elements below y and returns elements above y
-        let rhs = self
-            .elements
-            .extract_if(.., |element| element.top_left().y <= y)
-            .collect::<Vec<PrimitiveBox>>();
-        if rhs.is_empty() {
-            return None;
+    fn spawn(&mut self, kind: PieceKind) {
+        self.falling_piece = Piece::spawn(kind);
+        self.gravity_timer = 0;
+        self.can_hold = true;
+        if !self.board.fits(&self.falling_piece) {
+            self.game_over = true;
+            self.emit(Event::GameOver);
+            return;
         }
+        self.emit(Event::PieceSpawned(self.falling_piece.clone()));
+    }
+}
 
-        Some(Shape {
-            elements: rhs,
-            pivot: Point::zero(),
-        })
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use piece::{Cell, PieceKind, SPAWN_COL};
+
+    fn state_with(kind: PieceKind) -> GameState {
+        let mut state = GameState::new(0);
+        state.falling_piece = Piece::spawn(kind);
+        state
     }
 
-    fn remove<F>(&mut self, condition: F)
-    where
-        F: Fn(&PrimitiveBox) -> bool,
-    {
-        self.elements.retain(|element| !condition(element));
-    }
-
-    #[allow(dead_code)]
-    fn recompute_pivot(&mut self) {
-        let mut pivot = self.bounding_box().center();
-        snap_to_grid(&mut pivot, 1);
-        self.pivot = pivot
-    }
-
-    fn rotate_clockwise<F>(&mut self, f: F) -> Option<()>
-    where
-        F: Fn(&PrimitiveBox) -> bool,
-    {
-        self.rotate(f, -1, 1)
-    }
-
-    fn rotate_counterclockwise<F>(&mut self, f: F) -> Option<()>
-    where
-        F: Fn(&PrimitiveBox) -> bool,
-    {
-        self.rotate(f, 1, -1)
-    }
-
-    fn rotate<F>(&mut self, f: F, x_mul: i32, y_mul: i32) -> Option<()>
-    where
-        F: Fn(&PrimitiveBox) -> bool,
-    {
-        let multiplicator = 2;
-        let center = self
-            .pivot
-            .component_mul(Point::new(multiplicator, multiplicator));
-        let mut clone = self.clone();
-        for element in clone.elements.iter_mut() {
-            let x = element.top_left().x * multiplicator - center.x;
-            let y = element.top_left().y * multiplicator - center.y;
-            element.inner.top_left.x = x_mul * y + center.x;
-            element.inner.top_left.y = y_mul * x + center.y;
-            snap_to_grid(&mut element.inner.top_left, multiplicator);
-            element.inner.top_left.x /= multiplicator;
-            element.inner.top_left.y /= multiplicator;
-            if !element.is_in_bounds() || f(element) {
-                return None;
+    fn fill(state: &mut GameState, rows: impl Iterator<Item = usize>, cols: &[i32]) {
+        for row in rows {
+            for &col in cols {
+                state
+                    .board
+                    .set(Cell::new(col, row as i32), Some(PieceKind::Quad));
             }
-            assert_eq!(element.top_left().x % GRANULE_SIZE, 0);
-            assert_eq!(element.top_left().y % GRANULE_SIZE, 0);
         }
-        self.elements = clone.elements;
-        Some(())
     }
 
-    fn new(elements: Vec<PrimitiveBox>) -> Self {
-        let mut s = Self {
-            elements,
-            pivot: Point::zero(),
-        };
-        s.recompute_pivot();
-        s
-    }
-
-    fn left_checked<F>(&mut self, f: F) -> Option<()>
-    where
-        F: Fn(&Shape) -> bool,
-    {
-        let mut clone = self.clone();
-        clone.left()?;
-        if !f(&clone) {
-            *self = clone;
-            return Some(());
-        }
-        None
-    }
-
-    fn right_checked<F>(&mut self, f: F) -> Option<()>
-    where
-        F: Fn(&Shape) -> bool,
-    {
-        let mut clone = self.clone();
-        clone.right()?;
-        if !f(&clone) {
-            *self = clone;
-            return Some(());
-        }
-        None
+    fn all_cols_except(except: &[i32]) -> Vec<i32> {
+        (0..COLS as i32)
+            .filter(|col| !except.contains(col))
+            .collect()
     }
-}
 
-impl Object for Shape {
-    fn left(&mut self) -> Option<()> {
-        if let Some(leftmost) = self.leftmost()
-            && leftmost.top_left().x > X_ANCHOR
+    fn drop_piece(state: &mut GameState) -> Vec<Event> {
+        let mut events = Vec::new();
+        while !events
+            .iter()
+            .any(|event| matches!(event, Event::PieceLocked(_)))
         {
-            self.pivot.x -= GRANULE_SIZE;
-            for element in self.elements.iter_mut() {
-                element.left();
-            }
-            return Some(());
+            events.extend(state.step(&[Action::SoftDrop], 0));
         }
-        None
+        events
     }
 
-    fn right(&mut self) -> Option<()> {
-        if let Some(rightmost) = self.rightmost()
-            && rightmost.top_left().x + (rightmost.bounding_box().size.width as i32) < MAX_X
-        {
-            self.pivot.x += GRANULE_SIZE;
-            for element in self.elements.iter_mut() {
-                element.right();
-            }
-            return Some(());
-        }
-        None
+    fn cleared(events: &[Event]) -> Option<ClearKind> {
+        events.iter().find_map(|event| match event {
+            Event::LinesCleared { kind, .. } => Some(*kind),
+            _ => None,
+        })
     }
 
-    fn down(&mut self) -> Option<()> {
-        if let Some(lowest) = self.lowest()
-            && lowest.top_left().y + (lowest.bounding_box().size.height as i32) < MAX_Y
-        {
-            self.pivot.y += GRANULE_SIZE;
-            for element in self.elements.iter_mut() {
-                element.down();
-            }
-            return Some(());
-        }
-        None
+    fn cols_of(piece: &Piece) -> Vec<i32> {
+        let mut cols = piece.cells().map(|cell| cell.col).to_vec();
+        cols.sort();
+        cols.dedup();
+        cols
+    }
+
+    #[test]
+    fn gravity_moves_the_piece_once_per_interval() {
+        let mut state = state_with(PieceKind::T);
+        let start = state.falling_piece.clone();
+
+        state.step(&[], GRAVITY_INTERVAL - 1);
+        assert_eq!(state.falling_piece, start);
+        state.step(&[], 1);
+        assert_eq!(state.falling_piece, start.shifted(0, 1));
+        state.step(&[], 3 * GRAVITY_INTERVAL);
+        assert_eq!(state.falling_piece, start.shifted(0, 4));
     }
 
-    fn draw(&self) {
-        for element in self.elements.iter() {
-            element.draw();
+    #[test]
+    fn pieces_stop_at_the_walls() {
+        let mut state = state_with(PieceKind::Quad);
+        for _ in 0..COLS {
+            state.step(&[Action::Left], 0);
+        }
+        assert_eq!(cols_of(&state.falling_piece), [0, 1]);
+        for _ in 0..COLS {
+            state.step(&[Action::Right], 0);
         }
+        assert_eq!(
+            cols_of(&state.falling_piece),
+            [COLS as i32 - 2, COLS as i32 - 1]
+        );
     }
 
-    fn bounding_box(&self) -> Rectangle {
-        if let Some(lowest) = self.lowest()
-            && let Some(rightmost) = self.rightmost()
-            && let Some(leftmost) = self.leftmost()
-            && let Some(highest) = self.highest()
-        {
-            Rectangle::new(
-                Point::new(leftmost.top_left().x, highest.top_left().y),
-                Size::new(
-                    rightmost.top_left().x as u32 + rightmost.bounding_box().size.width
-                        - leftmost.top_left().x as u32,
-                    lowest.top_left().y as u32 + lowest.bounding_box().size.height
-                        - highest.top_left().y as u32,
-                ),
-            )
-        } else {
-            Rectangle::zero()
+    #[test]
+    fn rotation_kicks_off_the_wall() {
+        let mut state = state_with(PieceKind::Long);
+        for _ in 0..COLS {
+            state.step(&[Action::Left], 0);
         }
+        assert_eq!(cols_of(&state.falling_piece), [0]);
+
+        state.step(&[Action::RotateClockwise], 0);
+        assert_eq!(cols_of(&state.falling_piece), [0, 1, 2, 3]);
     }
 
-    fn top_left(&self) -> Point {
-        self.bounding_box().top_left
+    #[test]
+    fn rotation_is_rejected_when_no_kick_fits() {
+        let mut state = state_with(PieceKind::Long);
+        fill(&mut state, 0..LINES, &[9, 11]);
+        let start = state.falling_piece.clone();
+
+        state.step(&[Action::RotateClockwise], 0);
+        assert_eq!(state.falling_piece, start);
+        state.step(&[Action::RotateCounterclockwise], 0);
+        assert_eq!(state.falling_piece, start);
     }
-}
 
-struct ShapeBuilder {
-    inner: Shape,
-}
+    #[test]
+    fn rotation_kicks_around_blocks() {
+        let mut state = state_with(PieceKind::T);
+        state.step(&[Action::RotateClockwise], 0);
+        let upright = state.falling_piece.clone();
+        // block the cell the flat T would need to the left of its stem
+        let blocked = Cell::new(SPAWN_COL - 1, 1);
+        fill(&mut state, 1..2, &[blocked.col]);
 
-impl ShapeBuilder {
-    fn long() -> Self {
-        let color = Rgb888::RED;
-        Self {
-            inner: Shape::new(vec![
-                PrimitiveBox::new(
-                    X_ANCHOR + (MAX_X - X_ANCHOR) / 2,
-                    Y_ANCHOR + 3 * GRANULE_SIZE,
-                    color,
-                ),
-                PrimitiveBox::new(
-                    X_ANCHOR + (MAX_X - X_ANCHOR) / 2,
-                    Y_ANCHOR + 2 * GRANULE_SIZE,
-                    color,
-                ),
-                PrimitiveBox::new(
-                    X_ANCHOR + (MAX_X - X_ANCHOR) / 2,
-                    Y_ANCHOR + GRANULE_SIZE,
-                    color,
-                ),
-                PrimitiveBox::new(X_ANCHOR + (MAX_X - X_ANCHOR) / 2, Y_ANCHOR, color),
-            ]),
-        }
+        state.step(&[Action::RotateCounterclockwise], 0);
+        assert_ne!(state.falling_piece, upright);
+        assert!(
+            state
+                .falling_piece
+                .cells()
+                .iter()
+                .all(|cell| *cell != blocked)
+        );
+        assert!(state.board.fits(&state.falling_piece));
     }
 
-    fn quad() -> Self {
-        let color = Rgb888::GREEN;
+    #[test]
+    fn pieces_land_on_overhangs() {
+        let mut state = state_with(PieceKind::Long);
+        // a ledge with empty space beneath it
+        fill(&mut state, 15..16, &[5, 6, 7, 8, 9, 10]);
 
-        Self {
-            inner: Shape::new(vec![
-                PrimitiveBox::new(
-                    X_ANCHOR + (MAX_X - X_ANCHOR) / 2,
-                    Y_ANCHOR + GRANULE_SIZE,
-                    color,
-                ),
-                PrimitiveBox::new(
-                    X_ANCHOR + (MAX_X - X_ANCHOR) / 2 + GRANULE_SIZE,
-                    Y_ANCHOR + GRANULE_SIZE,
-                    color,
-                ),
-                PrimitiveBox::new(X_ANCHOR + (MAX_X - X_ANCHOR) / 2, Y_ANCHOR, color),
-                PrimitiveBox::new(
-                    X_ANCHOR + (MAX_X - X_ANCHOR) / 2 + GRANULE_SIZE,
-                    Y_ANCHOR,
-                    color,
-                ),
-            ]),
-        }
+        drop_piece(&mut state);
+
+        let rows = state.board.rows();
+        let col = SPAWN_COL as usize;
+        assert!(
+            rows[11..15]
+                .iter()
+                .all(|row| row[col] == Some(PieceKind::Long))
+        );
+        assert!(rows[16..].iter().all(|row| row[col].is_none()));
     }
 
-    fn t() -> Self {
-        let color = Rgb888::MAGENTA;
+    #[test]
+    fn ghost_shows_where_the_piece_locks() {
+        let mut state = state_with(PieceKind::Long);
+        fill(&mut state, 15..16, &[SPAWN_COL]);
+        let ghost = state.ghost_piece();
+        assert_eq!(ghost, state.falling_piece.shifted(0, 11));
 
-        Self {
-            inner: Shape::new(vec![
-                PrimitiveBox::new(
-                    X_ANCHOR + (MAX_X - X_ANCHOR) / 2,
-                    Y_ANCHOR + GRANULE_SIZE,
-                    color,
-                ),
-                PrimitiveBox::new(
-                    X_ANCHOR + (MAX_X - X_ANCHOR) / 2 + GRANULE_SIZE,
-                    Y_ANCHOR + GRANULE_SIZE,
-                    color,
-                ),
-                PrimitiveBox::new(
-                    X_ANCHOR + (MAX_X - X_ANCHOR) / 2 - GRANULE_SIZE,
-                    Y_ANCHOR + GRANULE_SIZE,
-                    color,
-                ),
-                PrimitiveBox::new(X_ANCHOR + (MAX_X - X_ANCHOR) / 2, Y_ANCHOR, color),
-            ]),
-        }
+        let events = drop_piece(&mut state);
+        assert!(events.contains(&Event::PieceLocked(ghost)));
     }
 
-    fn z() -> Self {
-        let color = Rgb888::BLUE;
+    #[test]
+    fn pieces_slide_under_overhangs() {
+        let mut state = state_with(PieceKind::Quad);
+        fill(&mut state, 15..16, &[5, 6, 7, 8, 9, 10]);
+        state.falling_piece = Piece::spawn(PieceKind::Quad).shifted(2, LINES as i32 - 2);
 
-        Self {
-            inner: Shape::new(vec![
-                PrimitiveBox::new(
-                    X_ANCHOR + (MAX_X - X_ANCHOR) / 2 + GRANULE_SIZE,
-                    Y_ANCHOR + GRANULE_SIZE,
-                    color,
-                ),
-                PrimitiveBox::new(
-                    X_ANCHOR + (MAX_X - X_ANCHOR) / 2 + 2 * GRANULE_SIZE,
-                    Y_ANCHOR + GRANULE_SIZE,
-                    color,
-                ),
-                PrimitiveBox::new(X_ANCHOR + (MAX_X - X_ANCHOR) / 2, Y_ANCHOR, color),
-                PrimitiveBox::new(
-                    X_ANCHOR + (MAX_X - X_ANCHOR) / 2 + GRANULE_SIZE,
-                    Y_ANCHOR,
-                    color,
-                ),
-            ]),
+        for _ in 0..COLS {
+            state.step(&[Action::Left], 0);
         }
+        assert_eq!(cols_of(&state.falling_piece), [0, 1]);
+
+        // the ledge blocks sideways movement one row further up
+        state.falling_piece = Piece::spawn(PieceKind::Quad).shifted(2, 14);
+        state.step(&[Action::Left], 0);
+        assert_eq!(
+            cols_of(&state.falling_piece),
+            [SPAWN_COL + 1, SPAWN_COL + 2]
+        );
     }
 
-    fn l() -> Self {
-        let color = Rgb888::YELLOW;
+    #[test]
+    fn completed_lines_are_cleared_and_scored() {
+        let mut state = state_with(PieceKind::Long);
+        let cols = all_cols_except(&[SPAWN_COL]);
+        fill(&mut state, LINES - 2..LINES, &cols);
 
-        Self {
-            inner: Shape::new(vec![
-                PrimitiveBox::new(
-                    X_ANCHOR + (MAX_X - X_ANCHOR) / 2,
-                    Y_ANCHOR + 2 * GRANULE_SIZE,
-                    color,
-                ),
-                PrimitiveBox::new(
-                    X_ANCHOR + (MAX_X - X_ANCHOR) / 2 + GRANULE_SIZE,
-                    Y_ANCHOR + 2 * GRANULE_SIZE,
-                    color,
-                ),
-                PrimitiveBox::new(
-                    X_ANCHOR + (MAX_X - X_ANCHOR) / 2,
-                    Y_ANCHOR + GRANULE_SIZE,
-                    color,
-                ),
-                PrimitiveBox::new(X_ANCHOR + (MAX_X - X_ANCHOR) / 2, Y_ANCHOR, color),
-            ]),
+        let events = drop_piece(&mut state);
+
+        assert_eq!(cleared(&events), Some(ClearKind::Double));
+        assert_eq!(state.score.points(), 2 * COLS as u32);
+        let rows = state.board.rows();
+        assert_eq!(rows[LINES - 1][SPAWN_COL as usize], Some(PieceKind::Long));
+        assert_eq!(rows[LINES - 2][SPAWN_COL as usize], Some(PieceKind::Long));
+        assert_eq!(
+            rows[LINES - 1].iter().filter(|cell| cell.is_some()).count(),
+            1
+        );
+        assert_eq!(rows[LINES - 3], [None; COLS]);
+    }
+
+    #[test]
+    fn completed_lines_wait_for_the_line_clear_delay() {
+        let mut state = state_with(PieceKind::Long);
+        state.set_line_clear_delay(300);
+        fill(&mut state, LINES - 1..LINES, &all_cols_except(&[SPAWN_COL]));
+
+        let events = drop_piece(&mut state);
+        assert_eq!(cleared(&events), None);
+        assert_eq!(state.clearing().unwrap().lines(), [LINES - 1]);
+        assert!(state.board.full_lines() == [LINES - 1]);
+
+        // the falling piece is out of play until the delay passed
+        let locked = state.falling_piece.clone();
+        assert!(state.step(&[], 200).is_empty());
+        assert_eq!(state.falling_piece, locked);
+        assert_eq!(state.clearing().unwrap().elapsed(), 200);
+
+        let events = state.step(&[], 100);
+        assert_eq!(cleared(&events), Some(ClearKind::Single));
+        assert!(matches!(events.last(), Some(Event::PieceSpawned(_))));
+        assert_eq!(state.clearing(), None);
+    }
+
+    #[test]
+    fn any_action_skips_the_line_clear_delay() {
+        let mut state = state_with(PieceKind::Long);
+        state.set_line_clear_delay(300);
+        fill(&mut state, LINES - 1..LINES, &all_cols_except(&[SPAWN_COL]));
+        drop_piece(&mut state);
+
+        let events = state.step(&[Action::Left], 0);
+        assert_eq!(cleared(&events), Some(ClearKind::Single));
+        assert!(matches!(events.last(), Some(Event::PieceMoved { .. })));
+
+        state.restart();
+        assert_eq!(state.line_clear_delay(), 300);
+    }
+
+    #[test]
+    fn non_contiguous_lines_clear_together() {
+        let mut state = state_with(PieceKind::Long);
+        let cols = all_cols_except(&[SPAWN_COL]);
+        fill(&mut state, LINES - 4..LINES - 3, &cols);
+        fill(&mut state, LINES - 3..LINES - 2, &cols[1..]);
+        fill(&mut state, LINES - 2..LINES, &cols);
+
+        let events = drop_piece(&mut state);
+
+        assert_eq!(cleared(&events), Some(ClearKind::Triple));
+        assert_eq!(state.score.points(), 3 * COLS as u32);
+        let rows = state.board.rows();
+        // only the partial line is left, with the unused part of the piece on top of it
+        assert_eq!(rows[LINES - 1][0], None);
+        assert_eq!(rows[LINES - 1][SPAWN_COL as usize], Some(PieceKind::Long));
+        assert_eq!(
+            rows[LINES - 1].iter().filter(|cell| cell.is_some()).count(),
+            COLS - 1
+        );
+        assert_eq!(rows[LINES - 2], [None; COLS]);
+    }
+
+    #[test]
+    fn blocked_spawn_tops_out() {
+        let mut state = state_with(PieceKind::Long);
+        fill(&mut state, 4..LINES, &all_cols_except(&[0]));
+
+        let events = drop_piece(&mut state);
+
+        assert_eq!(events.last(), Some(&Event::GameOver));
+        assert!(state.game_over);
+        let elapsed = state.elapsed();
+        assert!(state.step(&[Action::SoftDrop], GRAVITY_INTERVAL).is_empty());
+        assert_eq!(state.elapsed(), elapsed);
+
+        state.restart();
+        assert!(!state.game_over);
+        assert_eq!(state.score.points(), 0);
+        assert_eq!(state.elapsed(), 0);
+        assert!(state.board.rows().iter().flatten().all(Option::is_none));
+    }
+
+    #[test]
+    fn same_seed_plays_the_same_game() {
+        let actions = [
+            Action::Left,
+            Action::RotateClockwise,
+            Action::SoftDrop,
+            Action::Right,
+        ];
+        let mut lhs = GameState::new(3);
+        let mut rhs = GameState::new(3);
+        for i in 0..500 {
+            let action = [actions[i % actions.len()]];
+            assert_eq!(lhs.step(&action, 97), rhs.step(&action, 97));
         }
+        assert_eq!(lhs, rhs);
     }
 
-    fn build(self) -> Shape {
-        self.inner
+    #[test]
+    fn locking_reports_the_lock_clear_and_next_spawn_in_order() {
+        let mut state = state_with(PieceKind::Long);
+        fill(&mut state, LINES - 1..LINES, &all_cols_except(&[SPAWN_COL]));
+        state.step(&[], 0);
+
+        let events = drop_piece(&mut state);
+
+        let tail = &events[events.len() - 3..];
+        assert!(matches!(&tail[0], Event::PieceLocked(piece) if piece.kind() == PieceKind::Long));
+        assert_eq!(
+            tail[1],
+            Event::LinesCleared {
+                lines: alloc::vec![LINES - 1],
+                kind: ClearKind::Single
+            }
+        );
+        assert_eq!(tail[2], Event::PieceSpawned(state.falling_piece.clone()));
+    }
+
+    #[test]
+    fn moves_report_both_positions() {
+        let mut state = state_with(PieceKind::T);
+        let start = state.falling_piece.clone();
+
+        let events = state.step(&[Action::Left, Action::Right], 0);
+
+        assert_eq!(
+            events[events.len() - 2..],
+            [
+                Event::PieceMoved {
+                    from: start.clone(),
+                    to: start.shifted(-1, 0)
+                },
+                Event::PieceMoved {
+                    from: start.shifted(-1, 0),
+                    to: start.clone()
+                },
+            ]
+        );
+        // blocked moves are not reported
+        state.falling_piece = start.shifted(-(SPAWN_COL - 1), 0);
+        assert!(state.step(&[Action::Left], 0).is_empty());
+    }
+
+    #[test]
+    fn hard_drop_locks_at_the_ghost() {
+        let mut state = state_with(PieceKind::T);
+        state.step(&[], 0);
+        let ghost = state.ghost_piece();
+
+        let events = state.step(&[Action::HardDrop], 0);
+        assert_eq!(
+            events.first(),
+            Some(&Event::PieceMoved {
+                from: Piece::spawn(PieceKind::T),
+                to: ghost.clone(),
+            })
+        );
+        assert!(events.contains(&Event::PieceLocked(ghost)));
+        assert!(matches!(events.last(), Some(Event::PieceSpawned(_))));
+    }
+
+    #[test]
+    fn rotating_by_half_a_turn_equals_two_quarter_turns() {
+        let mut state = state_with(PieceKind::L);
+        state.step(&[Action::SoftDrop; 3], 0);
+        let turned = state
+            .falling_piece
+            .rotated(Rotation::Clockwise)
+            .rotated(Rotation::Clockwise);
+
+        state.step(&[Action::Rotate180], 0);
+        assert_eq!(state.falling_piece, turned);
     }
 
-    #[allow(dead_code)]
-    fn with_color(mut self, color: Rgb888) -> Self {
-        self.inner.elements.iter_mut().for_each(|element| {
-            element.style = PrimitiveStyleBuilder::new()
-                .fill_color(color)
-                .stroke_color(color)
-                .build()
-        });
-        self
+    #[test]
+    fn pausing_stops_time_and_actions() {
+        let mut state = state_with(PieceKind::T);
+        state.step(&[], 0);
+        let falling = state.falling_piece.clone();
+
+        assert_eq!(
+            state.step(&[Action::Pause, Action::Left], 0),
+            [Event::Paused]
+        );
+        assert!(state.is_paused());
+        assert!(
+            state
+                .step(&[Action::Left, Action::Hold], GRAVITY_INTERVAL * 4)
+                .is_empty()
+        );
+        assert_eq!(state.falling_piece, falling);
+        assert_eq!(state.elapsed(), 0);
+        assert_eq!(state.clock(), GRAVITY_INTERVAL * 4);
+
+        let events = state.step(&[Action::Pause, Action::Left], 0);
+        assert_eq!(events[0], Event::Resumed);
+        assert!(matches!(events[1], Event::PieceMoved { .. }));
+        assert!(!state.is_paused());
+    }
+
+    #[test]
+    fn hold_swaps_once_per_piece() {
+        let mut state = state_with(PieceKind::T);
+        state.step(&[], 0);
+        let next = state.next_kind();
+
+        let events = state.step(&[Action::Hold], 0);
+        assert_eq!(events[0], Event::Hold(PieceKind::T));
+        assert!(matches!(events[1], Event::PieceSpawned(_)));
+        assert_eq!(state.held_piece(), Some(PieceKind::T));
+        let first_from_randomizer = state.falling_piece.kind();
+        assert_eq!(first_from_randomizer, next);
+
+        // holding again before the piece locked does nothing
+        assert!(state.step(&[Action::Hold], 0).is_empty());
+        assert_eq!(state.falling_piece.kind(), first_from_randomizer);
+
+        drop_piece(&mut state);
+        state.step(&[Action::Hold], 0);
+        assert_eq!(state.falling_piece, Piece::spawn(PieceKind::T));
+        assert_ne!(state.held_piece(), Some(PieceKind::T));
+    }
+
+    #[test]
+    fn clearing_enough_lines_levels_up_and_speeds_up_gravity() {
+        let mut state = state_with(PieceKind::Long);
+        let cols = all_cols_except(&[SPAWN_COL]);
+        let mut level_ups = Vec::new();
+        for _ in 0..3 {
+            state.falling_piece = Piece::spawn(PieceKind::Long);
+            fill(&mut state, LINES - 4..LINES, &cols);
+            let events = drop_piece(&mut state);
+            assert_eq!(cleared(&events), Some(ClearKind::Tetris));
+            level_ups.extend(
+                events
+                    .into_iter()
+                    .filter(|e| matches!(e, Event::LevelUp(_))),
+            );
+        }
+
+        assert_eq!(state.score.lines(), 12);
+        assert_eq!(level_ups, [Event::LevelUp(1)]);
+        assert!(state.gravity_interval() < GRAVITY_INTERVAL);
     }
 }
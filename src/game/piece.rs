@@ -0,0 +1,221 @@
+//! Piece shapes and their movement on the board grid.
+
+use super::COLS;
+
+/*
+pieces live on the board grid:
+    col 0 is the left wall, row 0 is the top of the board.
+the pivot is stored in doubled coordinates, such that it can sit either on a cell center (odd)
+or on a cell corner (even) and rotations stay on the grid.
+*/
+
+/// The column new pieces spawn around.
+pub const SPAWN_COL: i32 = COLS as i32 / 2;
+
+/// The shapes a piece can have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PieceKind {
+    /// Four blocks in a line.
+    Long,
+    /// A 2x2 square.
+    Quad,
+    /// Three blocks in a line with one on top of the center.
+    T,
+    /// Two offset pairs of blocks.
+    Z,
+    /// Three blocks in a line with one to the side of the end.
+    L,
+}
+
+impl PieceKind {
+    /// Every kind, in declaration order.
+    pub const ALL: [PieceKind; 5] = [
+        PieceKind::Long,
+        PieceKind::Quad,
+        PieceKind::T,
+        PieceKind::Z,
+        PieceKind::L,
+    ];
+
+    // cells and doubled pivot of the piece in its spawn orientation
+    fn spawn_layout(self) -> ([Cell; 4], Cell) {
+        let c = SPAWN_COL;
+        match self {
+            Self::Long => (
+                [
+                    Cell::new(c, 3),
+                    Cell::new(c, 2),
+                    Cell::new(c, 1),
+                    Cell::new(c, 0),
+                ],
+                Cell::new(2 * c, 4),
+            ),
+            Self::Quad => (
+                [
+                    Cell::new(c, 1),
+                    Cell::new(c + 1, 1),
+                    Cell::new(c, 0),
+                    Cell::new(c + 1, 0),
+                ],
+                Cell::new(2 * c + 2, 2),
+            ),
+            Self::T => (
+                [
+                    Cell::new(c, 1),
+                    Cell::new(c + 1, 1),
+                    Cell::new(c - 1, 1),
+                    Cell::new(c, 0),
+                ],
+                Cell::new(2 * c + 1, 3),
+            ),
+            Self::Z => (
+                [
+                    Cell::new(c + 1, 1),
+                    Cell::new(c + 2, 1),
+                    Cell::new(c, 0),
+                    Cell::new(c + 1, 0),
+                ],
+                Cell::new(2 * c + 3, 3),
+            ),
+            Self::L => (
+                [
+                    Cell::new(c, 2),
+                    Cell::new(c + 1, 2),
+                    Cell::new(c, 1),
+                    Cell::new(c, 0),
+                ],
+                Cell::new(2 * c + 1, 3),
+            ),
+        }
+    }
+}
+
+/// A position on the board grid.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cell {
+    /// Column, counted from the left wall.
+    pub col: i32,
+    /// Row, counted from the top of the board.
+    pub row: i32,
+}
+
+impl Cell {
+    /// Creates the cell at `col`, `row`.
+    pub const fn new(col: i32, row: i32) -> Self {
+        Self { col, row }
+    }
+}
+
+/// The direction of a rotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// A quarter turn clockwise.
+    Clockwise,
+    /// A quarter turn counterclockwise.
+    Counterclockwise,
+}
+
+/// A piece made of four cells, rotating around its pivot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Piece {
+    kind: PieceKind,
+    cells: [Cell; 4],
+    pivot: Cell,
+}
+
+impl Piece {
+    /// Creates a piece of `kind` in its spawn position at the top of the board.
+    pub fn spawn(kind: PieceKind) -> Self {
+        let (cells, pivot) = kind.spawn_layout();
+        Self { kind, cells, pivot }
+    }
+
+    /// The shape of this piece.
+    pub fn kind(&self) -> PieceKind {
+        self.kind
+    }
+
+    /// The cells covered by this piece.
+    pub fn cells(&self) -> &[Cell; 4] {
+        &self.cells
+    }
+
+    /// A copy of this piece moved by `cols` and `rows`.
+    pub fn shifted(&self, cols: i32, rows: i32) -> Self {
+        let mut clone = self.clone();
+        for cell in clone.cells.iter_mut() {
+            cell.col += cols;
+            cell.row += rows;
+        }
+        clone.pivot.col += 2 * cols;
+        clone.pivot.row += 2 * rows;
+        clone
+    }
+
+    /// A copy of this piece rotated around its pivot, without checking for collisions.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        let (x_mul, y_mul) = match rotation {
+            Rotation::Clockwise => (-1, 1),
+            Rotation::Counterclockwise => (1, -1),
+        };
+        let mut clone = self.clone();
+        for cell in clone.cells.iter_mut() {
+            // rotate the cell center around the pivot, both in doubled coordinates
+            let x = 2 * cell.col + 1 - self.pivot.col;
+            let y = 2 * cell.row + 1 - self.pivot.row;
+            cell.col = (x_mul * y + self.pivot.col - 1) / 2;
+            cell.row = (y_mul * x + self.pivot.row - 1) / 2;
+        }
+        clone
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn four_rotations_are_the_identity() {
+        for kind in PieceKind::ALL {
+            let piece = Piece::spawn(kind);
+            for rotation in [Rotation::Clockwise, Rotation::Counterclockwise] {
+                let turned = (0..4).fold(piece.clone(), |piece, _| piece.rotated(rotation));
+                assert_eq!(turned, piece, "{kind:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn counterclockwise_undoes_clockwise() {
+        for kind in PieceKind::ALL {
+            let piece = Piece::spawn(kind).shifted(-3, 5);
+            let turned = piece
+                .rotated(Rotation::Clockwise)
+                .rotated(Rotation::Counterclockwise);
+            assert_eq!(turned, piece, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn quad_does_not_move_when_rotated() {
+        let piece = Piece::spawn(PieceKind::Quad);
+        let mut cells = *piece.rotated(Rotation::Clockwise).cells();
+        let mut expected = *piece.cells();
+        cells.sort_by_key(|cell| (cell.row, cell.col));
+        expected.sort_by_key(|cell| (cell.row, cell.col));
+        assert_eq!(cells, expected);
+    }
+
+    #[test]
+    fn long_turns_horizontal() {
+        let piece = Piece::spawn(PieceKind::Long).rotated(Rotation::Clockwise);
+        let row = piece.cells()[0].row;
+        assert!(piece.cells().iter().all(|cell| cell.row == row));
+        let mut cols = piece.cells().map(|cell| cell.col);
+        cols.sort();
+        assert_eq!(
+            cols,
+            [SPAWN_COL - 2, SPAWN_COL - 1, SPAWN_COL, SPAWN_COL + 1]
+        );
+    }
+}
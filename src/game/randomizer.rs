@@ -0,0 +1,107 @@
+//! Deciding which piece comes next.
+
+use alloc::vec::Vec;
+use rand::{Rng, SeedableRng, rngs::SmallRng, seq::SliceRandom};
+
+use super::piece::PieceKind;
+
+/// The strategy a [`Randomizer`] deals pieces with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RandomizerKind {
+    /// Every piece is drawn independently.
+    #[default]
+    Uniform,
+    /// Every kind is dealt once per shuffled bag, so droughts are at most two bags long.
+    Bag,
+}
+
+/// A seeded, deterministic source of pieces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Randomizer {
+    kind: RandomizerKind,
+    seed: u64,
+    rng: SmallRng,
+    bag: Vec<PieceKind>,
+}
+
+impl Randomizer {
+    /// Creates a randomizer dealing with `kind`. The same seed always deals the same pieces.
+    pub fn new(kind: RandomizerKind, seed: u64) -> Self {
+        Self {
+            kind,
+            seed,
+            rng: SmallRng::seed_from_u64(seed),
+            bag: Vec::with_capacity(PieceKind::ALL.len()),
+        }
+    }
+
+    /// The strategy of this randomizer.
+    pub fn kind(&self) -> RandomizerKind {
+        self.kind
+    }
+
+    /// The seed this randomizer was created with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Deals the next piece.
+    pub fn next_kind(&mut self) -> PieceKind {
+        match self.kind {
+            RandomizerKind::Uniform => {
+                PieceKind::ALL[self.rng.random_range(..PieceKind::ALL.len())]
+            }
+            RandomizerKind::Bag => {
+                if self.bag.is_empty() {
+                    self.bag.extend_from_slice(&PieceKind::ALL);
+                    self.bag.shuffle(&mut self.rng);
+                }
+                self.bag.pop().unwrap_or(PieceKind::Long)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_deals_the_same_pieces() {
+        for kind in [RandomizerKind::Uniform, RandomizerKind::Bag] {
+            let mut lhs = Randomizer::new(kind, 7);
+            let mut rhs = Randomizer::new(kind, 7);
+            for _ in 0..100 {
+                assert_eq!(lhs.next_kind(), rhs.next_kind());
+            }
+        }
+    }
+
+    #[test]
+    fn every_piece_is_dealt_about_equally_often() {
+        const DRAWS: usize = 10_000;
+        let mut randomizer = Randomizer::new(RandomizerKind::Uniform, 42);
+        let mut counts = [0_usize; PieceKind::ALL.len()];
+        for _ in 0..DRAWS {
+            let kind = randomizer.next_kind();
+            counts[PieceKind::ALL.iter().position(|k| *k == kind).unwrap()] += 1;
+        }
+
+        let expected = DRAWS / PieceKind::ALL.len();
+        for count in counts {
+            assert!(count.abs_diff(expected) < expected / 10, "{counts:?}");
+        }
+    }
+
+    #[test]
+    fn every_bag_deals_each_piece_once() {
+        let mut randomizer = Randomizer::new(RandomizerKind::Bag, 42);
+        for _ in 0..100 {
+            let mut bag = (0..PieceKind::ALL.len())
+                .map(|_| randomizer.next_kind())
+                .collect::<Vec<_>>();
+            bag.sort_by_key(|kind| PieceKind::ALL.iter().position(|k| k == kind));
+            assert_eq!(bag, PieceKind::ALL);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Keeping score.
+
+use super::{
+    COLS,
+    event::{Event, Subscriber},
+};
+
+/// Lines to clear per level.
+pub const LINES_PER_LEVEL: u32 = 10;
+
+/// Points, cleared lines and level of a game. Every cleared line is worth [`COLS`] points.
+///
+/// The score follows the game through its [`Event`]s like any other [`Subscriber`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Score {
+    points: u32,
+    lines: u32,
+}
+
+impl Score {
+    /// The points scored so far.
+    pub fn points(&self) -> u32 {
+        self.points
+    }
+
+    /// The lines cleared so far.
+    pub fn lines(&self) -> u32 {
+        self.lines
+    }
+
+    /// The current level, starting at 0 and rising every [`LINES_PER_LEVEL`] lines.
+    pub fn level(&self) -> u32 {
+        self.lines / LINES_PER_LEVEL
+    }
+
+    fn award(&mut self, cleared: usize) {
+        self.points += (cleared * COLS) as u32;
+        self.lines += cleared as u32;
+    }
+}
+
+impl Subscriber for Score {
+    fn notify(&mut self, event: &Event) {
+        if let Event::LinesCleared { kind, .. } = event {
+            self.award(kind.lines());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_cleared_line_is_worth_a_row_of_blocks() {
+        let mut score = Score::default();
+        score.award(1);
+        score.award(4);
+        assert_eq!(score.points(), 5 * COLS as u32);
+        assert_eq!(score.lines(), 5);
+    }
+
+    #[test]
+    fn level_rises_every_ten_lines() {
+        let mut score = Score::default();
+        for _ in 0..9 {
+            score.award(1);
+        }
+        assert_eq!(score.level(), 0);
+        score.award(2);
+        assert_eq!(score.level(), 1);
+        for _ in 0..5 {
+            score.award(4);
+        }
+        assert_eq!(score.level(), 3);
+    }
+}
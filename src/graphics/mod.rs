@@ -1,8 +1,17 @@
+use alloc::vec::Vec;
 use conquer_once::spin::OnceCell;
 use spin::{Mutex, MutexGuard};
+use tiny_tetris::{
+    embedded_graphics::image::{Image, ImageRaw},
+    render::{RenderError, buffer::Present},
+};
 use tinygraphics::{
-    backend::{KernelFBWrapper, PrimitiveDrawer},
+    Pixel,
+    backend::{GraphicsBackend, KernelFBWrapper, PrimitiveDrawer},
+    draw_target::DrawTarget,
     pixelcolor::Rgb888,
+    prelude::{Dimensions, Primitive, RgbColor},
+    primitives::{PrimitiveStyle, Rectangle},
 };
 
 static GRAPHICS: OnceCell<GraphicsHandler<'static>> = OnceCell::uninit();
@@ -13,8 +22,8 @@ pub fn init_gfx() {
     GRAPHICS.init_once(GraphicsHandler::new);
 }
 
-pub fn graphics<'a>() -> &'a GraphicsHandler<'static> {
-    GRAPHICS.get().unwrap()
+pub fn graphics() -> Result<&'static GraphicsHandler<'static>, RenderError> {
+    GRAPHICS.get().ok_or(RenderError::Uninitialized)
 }
 
 pub struct GraphicsHandler<'a> {
@@ -32,4 +41,75 @@ impl<'a> GraphicsHandler<'a> {
     pub fn inner<'lock>(&'lock self) -> MutexGuard<'lock, Backend<'a>> {
         self.drawer.lock()
     }
+
+    // frees the drawer if a panic left it locked, the panicked holder never touches it again
+    pub fn recover(&self) {
+        if self.drawer.is_locked() {
+            // SAFETY: only called from the panic hook, after the only thread drawing stopped
+            unsafe { self.drawer.force_unlock() };
+        }
+    }
+}
+
+// draw target for the renderer, forwarding everything to the kernel fb through `graphics()`
+pub struct KernelTarget(&'static GraphicsHandler<'static>);
+
+impl KernelTarget {
+    // fails unless `init_gfx` ran before
+    pub fn new() -> Result<Self, RenderError> {
+        graphics().map(Self)
+    }
+}
+
+impl Dimensions for KernelTarget {
+    fn bounding_box(&self) -> Rectangle {
+        self.0.inner().bounding_box()
+    }
+}
+
+impl DrawTarget for KernelTarget {
+    type Color = Rgb888;
+    type Error = RenderError;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let mut drawer = self.0.inner();
+        for pixel in pixels {
+            drawer
+                .draw_primitive(&pixel)
+                .map_err(|_| RenderError::Display)?;
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        // copy the whole area as a single image instead of pixel by pixel
+        let data = colors
+            .into_iter()
+            .flat_map(|color| [color.r(), color.g(), color.b()])
+            .collect::<Vec<_>>();
+        let raw = ImageRaw::<Rgb888>::new(&data, area.size.width);
+        self.0
+            .inner()
+            .draw_primitive(&Image::new(&raw, area.top_left))
+            .map_err(|_| RenderError::Display)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.0
+            .inner()
+            .draw_primitive(&area.into_styled(PrimitiveStyle::with_fill(color)))
+            .map_err(|_| RenderError::Display)
+    }
+}
+
+impl Present for KernelTarget {
+    fn present(&mut self) -> Result<(), RenderError> {
+        self.0.inner().flush().map_err(|_| RenderError::Display)
+    }
 }
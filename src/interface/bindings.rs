@@ -0,0 +1,339 @@
+//! Which keys trigger which [`Action`]s.
+//!
+//! Every action can have any number of keys, but a key triggers at most one action. Players change
+//! the defaults with a key file, one line per action listing its keys, e.g.
+//!
+//! ```text
+//! # lines starting with # are comments
+//! rotate-cw = x, Up
+//! hard-drop = Space
+//! pause =
+//! ```
+//!
+//! Actions not mentioned keep their default keys, an empty list unbinds the action. The keys of
+//! [`COMMANDS`] cannot be bound. The [`Rebinding`] screen changes bindings while playing.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use super::{COMMANDS, Key, KeyCode, Modifiers};
+use crate::{Action, pairs::pairs};
+
+/// Every action that can be bound, with its name in key files, in the order they are listed.
+pub const ACTIONS: [(&str, Action); 9] = [
+    ("left", Action::Left),
+    ("right", Action::Right),
+    ("soft-drop", Action::SoftDrop),
+    ("hard-drop", Action::HardDrop),
+    ("rotate-cw", Action::RotateClockwise),
+    ("rotate-ccw", Action::RotateCounterclockwise),
+    ("rotate-180", Action::Rotate180),
+    ("hold", Action::Hold),
+    ("pause", Action::Pause),
+];
+
+/// Keys mapped to the actions they trigger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bindings {
+    keys: Vec<(Key, Action)>,
+}
+
+/// The ways a key file can be wrong, each with the number of the offending line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum BindingsError {
+    /// A line is neither empty, a comment nor an `action = keys` pair.
+    #[error("line {0}: expected `action = keys`")]
+    Syntax(usize),
+    /// An action names nothing that can be bound.
+    #[error("line {0}: unknown action")]
+    UnknownAction(usize),
+    /// A key name could not be read.
+    #[error("line {0}: unknown key")]
+    UnknownKey(usize),
+    /// A key is one of the [`COMMANDS`].
+    #[error("line {0}: key is taken by a command")]
+    Command(usize),
+}
+
+impl Bindings {
+    /// No keys at all.
+    pub fn empty() -> Self {
+        Self { keys: Vec::new() }
+    }
+
+    /// Reads a key file on top of the [defaults](Self::default).
+    pub fn parse(text: &str) -> Result<Self, BindingsError> {
+        let mut bindings = Self::default();
+        for pair in pairs(text.lines()) {
+            let (number, name, keys) = pair.map_err(BindingsError::Syntax)?;
+            bindings.set(name, keys, number)?;
+        }
+        Ok(bindings)
+    }
+
+    // replaces the keys of the action called `name` with the list `keys` from line `number`
+    pub(crate) fn set(
+        &mut self,
+        name: &str,
+        keys: &str,
+        number: usize,
+    ) -> Result<(), BindingsError> {
+        let action = named(name).ok_or(BindingsError::UnknownAction(number))?;
+        self.unbind(action);
+        for key in keys.split(',').map(str::trim).filter(|key| !key.is_empty()) {
+            let key = Key::named(key).ok_or(BindingsError::UnknownKey(number))?;
+            if COMMANDS.contains(&key) {
+                return Err(BindingsError::Command(number));
+            }
+            self.bind(key, action);
+        }
+        Ok(())
+    }
+
+    /// Writes the bindings as a key file, which [`parse`](Self::parse) reads back.
+    pub fn to_file(&self) -> String {
+        let mut file = String::new();
+        for (name, action) in ACTIONS {
+            file.push_str(&format!("{name} = {}\n", self.describe(action)));
+        }
+        file
+    }
+
+    /// The action triggered by `key`, if any. Keys other than characters also trigger the action
+    /// of the plain key when held with modifiers nothing is bound to.
+    pub fn action(&self, key: &Key) -> Option<Action> {
+        let bound = |key: &Key| {
+            self.keys
+                .iter()
+                .find(|(bound, _)| bound == key)
+                .map(|(_, action)| *action)
+        };
+        bound(key).or_else(|| {
+            if matches!(key.code, KeyCode::Char(_)) {
+                None
+            } else {
+                bound(&key.code.into())
+            }
+        })
+    }
+
+    /// The keys triggering `action`, in the order they were bound.
+    pub fn keys(&self, action: Action) -> impl Iterator<Item = &Key> {
+        self.keys
+            .iter()
+            .filter(move |(_, bound)| *bound == action)
+            .map(|(key, _)| key)
+    }
+
+    /// The keys of `action` as listed in key files.
+    pub fn describe(&self, action: Action) -> String {
+        self.keys(action)
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Makes `key` trigger `action`, instead of whatever it triggered before.
+    pub fn bind(&mut self, key: Key, action: Action) {
+        self.keys.retain(|(bound, _)| *bound != key);
+        self.keys.push((key, action));
+    }
+
+    /// Removes all keys of `action`.
+    pub fn unbind(&mut self, action: Action) {
+        self.keys.retain(|(_, bound)| *bound != action);
+    }
+}
+
+impl Default for Bindings {
+    /// The arrows to move, `k` and `l` to rotate, `j` to turn around, space to hard drop, `c` to
+    /// hold and `p` or escape to pause.
+    fn default() -> Self {
+        let plain = |code| Key::new(code, Modifiers::NONE);
+        let mut bindings = Self::empty();
+        for (code, action) in [
+            (KeyCode::ArrowLeft, Action::Left),
+            (KeyCode::ArrowRight, Action::Right),
+            (KeyCode::ArrowDown, Action::SoftDrop),
+            (KeyCode::Char(' '), Action::HardDrop),
+            (KeyCode::Char('l'), Action::RotateClockwise),
+            (KeyCode::ArrowUp, Action::RotateClockwise),
+            (KeyCode::Char('k'), Action::RotateCounterclockwise),
+            (KeyCode::Char('j'), Action::Rotate180),
+            (KeyCode::Char('c'), Action::Hold),
+            (KeyCode::Char('p'), Action::Pause),
+            (KeyCode::Esc, Action::Pause),
+        ] {
+            bindings.bind(plain(code), action);
+        }
+        bindings
+    }
+}
+
+/// The action called `name` in key files.
+pub fn named(name: &str) -> Option<Action> {
+    ACTIONS
+        .iter()
+        .find(|(action, _)| *action == name)
+        .map(|(_, action)| *action)
+}
+
+/// The lines of the screen shown while the game is paused, see [`Renderer::draw_menu`].
+///
+/// [`Renderer::draw_menu`]: crate::Renderer::draw_menu
+pub fn pause_screen(bindings: &Bindings) -> Vec<String> {
+    Vec::from([
+        "paused".to_string(),
+        format!("{}: resume", bindings.describe(Action::Pause)),
+        format!("{}: change keys", super::REBIND),
+    ])
+}
+
+/// The rebinding screen: walks through all [`ACTIONS`], letting the player type new keys for each.
+///
+/// The first key typed for an action replaces its keys, more keys are added to them. Enter moves
+/// on to the next action, backspace unbinds the current one and escape ends rebinding early. These
+/// three keys can therefore only be bound with a key file, the keys of [`COMMANDS`] not at all.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rebinding {
+    bindings: Bindings,
+    // index into ACTIONS of the action waiting for keys
+    current: usize,
+    // whether the current action got a key already
+    typed: bool,
+}
+
+impl Rebinding {
+    /// Starts rebinding with the first action, keeping `bindings` until keys are typed.
+    pub fn new(bindings: Bindings) -> Self {
+        Self {
+            bindings,
+            current: 0,
+            typed: false,
+        }
+    }
+
+    /// The action waiting for keys, `None` once rebinding is done.
+    pub fn action(&self) -> Option<Action> {
+        ACTIONS.get(self.current).map(|(_, action)| *action)
+    }
+
+    /// Handles a key typed on the rebinding screen.
+    pub fn press(&mut self, key: Key) {
+        let Some(action) = self.action() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Enter => self.next(),
+            KeyCode::Esc => self.current = ACTIONS.len(),
+            KeyCode::Backspace => {
+                self.bindings.unbind(action);
+                self.typed = true;
+            }
+            _ if COMMANDS.contains(&key) => {}
+            _ => {
+                if !self.typed {
+                    self.bindings.unbind(action);
+                    self.typed = true;
+                }
+                self.bindings.bind(key, action);
+            }
+        }
+    }
+
+    /// The text of the screen, a line per action with the current one marked, see
+    /// [`Renderer::draw_menu`](crate::Renderer::draw_menu).
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::from(["enter: next  backspace: clear  esc: done".to_string()]);
+        for (idx, (name, action)) in ACTIONS.iter().enumerate() {
+            let marker = if idx == self.current { '>' } else { ' ' };
+            lines.push(format!(
+                "{marker} {name:<10} {}",
+                self.bindings.describe(*action)
+            ));
+        }
+        lines
+    }
+
+    /// The bindings as changed so far.
+    pub fn into_bindings(self) -> Bindings {
+        self.bindings
+    }
+
+    fn next(&mut self) {
+        self.current += 1;
+        self.typed = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str) -> Key {
+        Key::named(name).unwrap()
+    }
+
+    #[test]
+    fn key_files_replace_the_keys_of_listed_actions() {
+        let bindings = Bindings::parse("# mine\nrotate-cw = x, Ctrl+Up\n\npause =\n").unwrap();
+        assert_eq!(bindings.action(&key("x")), Some(Action::RotateClockwise));
+        assert_eq!(
+            bindings.action(&key("Ctrl+Up")),
+            Some(Action::RotateClockwise)
+        );
+        // the plain arrow lost its default binding
+        assert_eq!(bindings.action(&key("Up")), None);
+        assert_eq!(bindings.action(&key("l")), None);
+        assert_eq!(bindings.action(&key("p")), None);
+        assert_eq!(bindings.action(&key("Left")), Some(Action::Left));
+        assert_eq!(bindings.action(&key("Alt+Left")), Some(Action::Left));
+        assert_eq!(bindings.action(&key("Ctrl+c")), None);
+
+        let file = bindings.to_file();
+        assert_eq!(Bindings::parse(&file).unwrap().to_file(), file);
+    }
+
+    #[test]
+    fn broken_key_files_are_rejected() {
+        assert_eq!(Bindings::parse("left"), Err(BindingsError::Syntax(1)));
+        assert_eq!(
+            Bindings::parse("\njump = Space"),
+            Err(BindingsError::UnknownAction(2))
+        );
+        assert_eq!(
+            Bindings::parse("left = F13"),
+            Err(BindingsError::UnknownKey(1))
+        );
+        assert_eq!(
+            Bindings::parse("hold = c\nleft = a, b"),
+            Err(BindingsError::Command(2))
+        );
+    }
+
+    #[test]
+    fn rebinding_replaces_and_adds_keys() {
+        let mut rebinding = Rebinding::new(Bindings::default());
+        assert_eq!(rebinding.action(), Some(Action::Left));
+        rebinding.press(key("a"));
+        // commands keep their keys
+        rebinding.press(key("s"));
+        rebinding.press(key("Home"));
+        rebinding.press(key("Enter"));
+        // taken from the first action
+        rebinding.press(key("Home"));
+        rebinding.press(key("Enter"));
+        rebinding.press(key("Backspace"));
+        rebinding.press(key("Esc"));
+        assert_eq!(rebinding.action(), None);
+
+        let bindings = rebinding.into_bindings();
+        assert_eq!(bindings.describe(Action::Left), "a");
+        assert_eq!(bindings.describe(Action::Right), "Home");
+        assert_eq!(bindings.describe(Action::SoftDrop), "");
+        assert_eq!(bindings.describe(Action::Hold), "c");
+    }
+}
@@ -0,0 +1,394 @@
+//! A state machine decoding the bytes a terminal sends for key presses, including the escape
+//! sequences of special keys.
+//!
+//! Besides characters encoded as UTF-8, terminals send
+//! - control bytes for tab, enter, backspace and letters pressed with ctrl,
+//! - `ESC <key>` for a key pressed with alt,
+//! - `ESC O <final>` (SS3) for arrows, home, end and F1 to F4 on some terminals,
+//! - `ESC [ <params> <final>` (CSI) for everything else, where the optional second parameter
+//!   encodes the modifiers, e.g. `ESC [ 1 ; 5 C` for ctrl and the right arrow.
+//!
+//! A sequence, or a character, may be split over several reads. An escape byte is therefore only
+//! decoded as [`KeyCode::Esc`] once the input stayed quiet for [`ESCAPE_TIMEOUT`] without
+//! completing a sequence, see [`Decoder::wait`].
+
+use alloc::vec::Vec;
+
+use super::{Key, KeyCode, Modifiers};
+
+const ESC: u8 = 0x1B;
+// CSI sequences with more parameters are no keys and only decoded to find their end
+const MAX_PARAMS: usize = 4;
+
+/// How many milliseconds an unfinished sequence may go without its next byte before it is
+/// [flushed](Decoder::flush). Terminals send a sequence in one go, so only a key pressed on its
+/// own, like escape, waits this long.
+pub const ESCAPE_TIMEOUT: u32 = 50;
+
+/// Decodes keys from terminal input arriving in any number of reads, remembering unfinished
+/// escape sequences in between.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Decoder {
+    state: State,
+    params: Vec<u16>,
+    // the bytes of the character being decoded
+    utf8: Vec<u8>,
+    // milliseconds since the last byte of an unfinished sequence
+    quiet: u32,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum State {
+    #[default]
+    Ground,
+    // after an escape byte
+    Escape,
+    // after `ESC [`
+    Csi,
+    // after `ESC O`
+    Ss3,
+    // inside a character of `len` bytes, pressed with alt if `alt`
+    Utf8 {
+        len: usize,
+        alt: bool,
+    },
+}
+
+impl Decoder {
+    /// Decodes the keys completed by `bytes`.
+    pub fn decode(&mut self, bytes: &[u8]) -> Vec<Key> {
+        if !bytes.is_empty() {
+            self.quiet = 0;
+        }
+        let mut keys = Vec::new();
+        for &byte in bytes {
+            if matches!(self.state, State::Utf8 { .. }) && !is_continuation(byte) {
+                // the character was cut short, the byte starts something new
+                self.state = State::Ground;
+                keys.push(KeyCode::Unknown.into());
+            }
+            keys.extend(self.push(byte));
+        }
+        keys
+    }
+
+    /// Notes that no input arrived for `ms` milliseconds and [flushes](Self::flush) an unfinished
+    /// sequence once it has been quiet for [`ESCAPE_TIMEOUT`] in total, over any number of waits.
+    pub fn wait(&mut self, ms: u32) -> Option<Key> {
+        if self.state == State::Ground {
+            return None;
+        }
+        self.quiet = self.quiet.saturating_add(ms);
+        if self.quiet < ESCAPE_TIMEOUT {
+            return None;
+        }
+        self.flush()
+    }
+
+    /// Ends an unfinished sequence right away, e.g. at the end of the input. A lone escape byte
+    /// is the escape key, anything longer cannot be decoded.
+    pub fn flush(&mut self) -> Option<Key> {
+        self.quiet = 0;
+        let key = match self.state {
+            State::Ground => None,
+            State::Escape => Some(KeyCode::Esc.into()),
+            State::Csi | State::Ss3 | State::Utf8 { .. } => Some(KeyCode::Unknown.into()),
+        };
+        self.state = State::Ground;
+        key
+    }
+
+    fn push(&mut self, byte: u8) -> Option<Key> {
+        match self.state {
+            State::Ground if byte == ESC => {
+                self.state = State::Escape;
+                None
+            }
+            State::Ground => self.start(byte, Modifiers::NONE),
+            State::Escape => match byte {
+                b'[' => {
+                    self.state = State::Csi;
+                    self.params.clear();
+                    None
+                }
+                b'O' => {
+                    self.state = State::Ss3;
+                    None
+                }
+                // the first escape key was pressed on its own
+                ESC => Some(KeyCode::Esc.into()),
+                _ => {
+                    self.state = State::Ground;
+                    self.start(byte, Modifiers::ALT)
+                }
+            },
+            State::Csi => match byte {
+                b'0'..=b'9' => {
+                    if self.params.is_empty() {
+                        self.params.push(0);
+                    }
+                    if let Some(param) = self.params.last_mut() {
+                        *param = param
+                            .saturating_mul(10)
+                            .saturating_add((byte - b'0').into());
+                    }
+                    None
+                }
+                b';' => {
+                    if self.params.is_empty() {
+                        self.params.push(0);
+                    }
+                    if self.params.len() < MAX_PARAMS {
+                        self.params.push(0);
+                    }
+                    None
+                }
+                // private markers and intermediate bytes, no key uses them
+                0x20..=0x2F | 0x3A..=0x3F => None,
+                0x40..=0x7E => {
+                    self.state = State::Ground;
+                    Some(csi(byte, &self.params))
+                }
+                // a control byte aborts the sequence, an escape byte starts the next one
+                _ => {
+                    self.state = if byte == ESC {
+                        State::Escape
+                    } else {
+                        State::Ground
+                    };
+                    Some(KeyCode::Unknown.into())
+                }
+            },
+            State::Ss3 if byte == ESC => {
+                self.state = State::Escape;
+                Some(KeyCode::Unknown.into())
+            }
+            State::Ss3 => {
+                self.state = State::Ground;
+                Some(cursor_key(byte).unwrap_or(KeyCode::Unknown).into())
+            }
+            State::Utf8 { len, alt } => {
+                self.utf8.push(byte);
+                if self.utf8.len() < len {
+                    return None;
+                }
+                self.state = State::Ground;
+                let code = str::from_utf8(&self.utf8)
+                    .ok()
+                    .and_then(|text| text.chars().next())
+                    .map_or(KeyCode::Unknown, KeyCode::Char);
+                let modifiers = if alt { Modifiers::ALT } else { Modifiers::NONE };
+                Some(Key::new(code, modifiers))
+            }
+        }
+    }
+
+    // a key starting with `byte` outside of any escape sequence
+    fn start(&mut self, byte: u8, modifiers: Modifiers) -> Option<Key> {
+        let len = match byte {
+            0xC2..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF4 => 4,
+            _ => return Some(control(byte, modifiers)),
+        };
+        self.utf8.clear();
+        self.utf8.push(byte);
+        self.state = State::Utf8 {
+            len,
+            alt: modifiers.contains(Modifiers::ALT),
+        };
+        None
+    }
+}
+
+fn is_continuation(byte: u8) -> bool {
+    byte & 0b1100_0000 == 0b1000_0000
+}
+
+// a single byte key, either a control byte or an ASCII character
+fn control(byte: u8, modifiers: Modifiers) -> Key {
+    let (code, ctrl) = match byte {
+        b'\t' => (KeyCode::Tab, false),
+        b'\r' | b'\n' => (KeyCode::Enter, false),
+        0x08 | 0x7F => (KeyCode::Backspace, false),
+        0x00 => (KeyCode::Char(' '), true),
+        // ctrl clears the upper bits of the letters and `[\\]^_`
+        0x01..=0x1A => (KeyCode::Char((byte + b'a' - 1).into()), true),
+        0x1C..=0x1F => (KeyCode::Char((byte + b'@').into()), true),
+        _ if byte.is_ascii() => (KeyCode::Char(byte.into()), false),
+        _ => (KeyCode::Unknown, false),
+    };
+    let modifiers = if ctrl {
+        modifiers | Modifiers::CTRL
+    } else {
+        modifiers
+    };
+    Key::new(code, modifiers)
+}
+
+// the keys sent as SS3 or parameterless CSI sequences
+fn cursor_key(byte: u8) -> Option<KeyCode> {
+    Some(match byte {
+        b'A' => KeyCode::ArrowUp,
+        b'B' => KeyCode::ArrowDown,
+        b'C' => KeyCode::ArrowRight,
+        b'D' => KeyCode::ArrowLeft,
+        b'H' => KeyCode::Home,
+        b'F' => KeyCode::End,
+        b'P'..=b'S' => KeyCode::F(byte - b'P' + 1),
+        _ => return None,
+    })
+}
+
+// a complete CSI sequence ending with `last`
+fn csi(last: u8, params: &[u16]) -> Key {
+    let code = if last == b'~' {
+        match params.first().copied().unwrap_or(0) {
+            1 | 7 => KeyCode::Home,
+            2 => KeyCode::Insert,
+            3 => KeyCode::Delete,
+            4 | 8 => KeyCode::End,
+            5 => KeyCode::PageUp,
+            6 => KeyCode::PageDown,
+            n @ 11..=15 => KeyCode::F((n - 10) as u8),
+            n @ 17..=21 => KeyCode::F((n - 11) as u8),
+            n @ 23..=24 => KeyCode::F((n - 12) as u8),
+            _ => KeyCode::Unknown,
+        }
+    } else {
+        cursor_key(last).unwrap_or(KeyCode::Unknown)
+    };
+    // the second parameter is one more than the bits of the modifiers held
+    let modifiers = match params.get(1) {
+        Some(&param) if code != KeyCode::Unknown => {
+            Modifiers(param.saturating_sub(1) as u8 & 0b111)
+        }
+        _ => Modifiers::NONE,
+    };
+    Key::new(code, modifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(bytes: &[u8]) -> Vec<Key> {
+        super::super::parse_ansi(bytes)
+    }
+
+    #[test]
+    fn special_keys_are_decoded() {
+        assert_eq!(
+            keys(b"\x1b[A\x1bOD\x1b[H\x1b[4~\x1b[5~\x1b[3~\x1bOP\x1b[24~x"),
+            [
+                KeyCode::ArrowUp,
+                KeyCode::ArrowLeft,
+                KeyCode::Home,
+                KeyCode::End,
+                KeyCode::PageUp,
+                KeyCode::Delete,
+                KeyCode::F(1),
+                KeyCode::F(12),
+                KeyCode::Char('x'),
+            ]
+            .map(Key::from)
+        );
+    }
+
+    #[test]
+    fn modifier_parameters_are_decoded() {
+        assert_eq!(
+            keys(b"\x1b[1;5C\x1b[15;2~\x1bk"),
+            [
+                Key::new(KeyCode::ArrowRight, Modifiers::CTRL),
+                Key::new(KeyCode::F(5), Modifiers::SHIFT),
+                Key::new(KeyCode::Char('k'), Modifiers::ALT),
+            ]
+        );
+    }
+
+    #[test]
+    fn characters_are_decoded_as_utf8() {
+        assert_eq!(
+            keys("ä€😀".as_bytes()),
+            ['ä', '€', '😀'].map(|c| Key::from(KeyCode::Char(c)))
+        );
+        // a broken character does not swallow the next key
+        assert_eq!(
+            keys(b"\xe2\x82x\xff"),
+            [KeyCode::Unknown, KeyCode::Char('x'), KeyCode::Unknown].map(Key::from)
+        );
+
+        let mut decoder = Decoder::default();
+        assert_eq!(decoder.decode(&[0xC3]), []);
+        assert_eq!(decoder.decode(&[0xB6]), [KeyCode::Char('ö').into()]);
+    }
+
+    #[test]
+    fn control_bytes_are_decoded() {
+        assert_eq!(
+            keys(b"\t\r\x7f\x03\x1b\x01"),
+            [
+                KeyCode::Tab.into(),
+                KeyCode::Enter.into(),
+                KeyCode::Backspace.into(),
+                Key::new(KeyCode::Char('c'), Modifiers::CTRL),
+                Key::new(KeyCode::Char('a'), Modifiers::CTRL | Modifiers::ALT),
+            ]
+        );
+    }
+
+    #[test]
+    fn sequences_may_span_reads() {
+        let mut decoder = Decoder::default();
+        assert_eq!(decoder.decode(b"a\x1b"), [KeyCode::Char('a').into()]);
+        assert_eq!(decoder.decode(b"[1;"), []);
+        assert_eq!(
+            decoder.decode(b"3D"),
+            [Key::new(KeyCode::ArrowLeft, Modifiers::ALT)]
+        );
+
+        // an escape byte followed by a quiet read is the escape key
+        assert_eq!(decoder.decode(b"\x1b"), []);
+        assert_eq!(decoder.wait(ESCAPE_TIMEOUT), Some(KeyCode::Esc.into()));
+        assert_eq!(decoder.wait(ESCAPE_TIMEOUT), None);
+        assert_eq!(
+            keys(b"\x1b\x1b[B"),
+            [KeyCode::Esc, KeyCode::ArrowDown].map(Key::from)
+        );
+    }
+
+    #[test]
+    fn interrupted_sequences_keep_the_next_one() {
+        assert_eq!(
+            keys(b"\x1b[1\x1b[A\x1bO\x1b[B\x1b[2\tx"),
+            [
+                KeyCode::Unknown,
+                KeyCode::ArrowUp,
+                KeyCode::Unknown,
+                KeyCode::ArrowDown,
+                KeyCode::Unknown,
+                KeyCode::Char('x'),
+            ]
+            .map(Key::from)
+        );
+    }
+
+    #[test]
+    fn short_quiet_reads_keep_sequences_pending() {
+        // polls at the end of a frame wait only a millisecond or two
+        let mut decoder = Decoder::default();
+        assert_eq!(decoder.decode(b"\x1b["), []);
+        for _ in 0..ESCAPE_TIMEOUT - 1 {
+            assert_eq!(decoder.wait(1), None);
+        }
+        assert_eq!(decoder.decode(b"A"), [KeyCode::ArrowUp.into()]);
+
+        // the wait starts over with every byte
+        assert_eq!(decoder.decode(b"\x1b"), []);
+        assert_eq!(decoder.wait(ESCAPE_TIMEOUT - 1), None);
+        assert_eq!(decoder.decode(b"O"), []);
+        assert_eq!(decoder.wait(ESCAPE_TIMEOUT - 1), None);
+        assert_eq!(decoder.wait(1), Some(KeyCode::Unknown.into()));
+    }
+}
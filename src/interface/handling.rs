@@ -0,0 +1,298 @@
+//! Delayed auto shift (DAS), auto repeat rate (ARR) and the soft-drop factor: how held keys move
+//! the falling piece, independent of the key repeat of the terminal.
+//!
+//! Terminals only report presses, so a key counts as held while the terminal keeps repeating it.
+//! Presses following each other at the steady cadence of key repeat tell that the key is held, the
+//! repeats stopping that it was released. A quick press on its own could be a fast tap as well as
+//! the start of key repeat, so it only moves the piece once the next press shows it was no repeat.
+//! The first repeat of the terminal comes as late as a second tap would, so it moves the piece like
+//! a tap. Keys therefore only auto shift once the terminal repeats them, even with a shorter DAS.
+
+use alloc::vec::Vec;
+
+use crate::{Action, COLS};
+
+// a repeating key counts as released once it was not repeated for this long, a bit longer than
+// the slowest common repeat interval. Presses further apart than this are no repeats.
+const RELEASE: u32 = 100;
+// presses arrive with the frame they were typed in, so the gaps between repeats vary by a frame
+const JITTER: u32 = 20;
+// a pressed key counts as released if it was not repeated for this long, a bit longer than the
+// longest common delay before terminals start repeating
+const FIRST_REPEAT: u32 = 700;
+
+/// How held keys move the falling piece, all times in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Handling {
+    /// How long left or right must be held before the piece starts shifting on its own.
+    pub das: u32,
+    /// The time between two shifts once shifting on its own. 0 shifts to the wall at once.
+    pub arr: u32,
+    /// How many times faster than gravity the piece falls while soft drop is held.
+    pub soft_drop_factor: u32,
+}
+
+impl Default for Handling {
+    fn default() -> Self {
+        Self {
+            das: 170,
+            arr: 50,
+            soft_drop_factor: 20,
+        }
+    }
+}
+
+/// Turns the actions of held keys into [`Handling`] conforming moves.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AutoRepeat {
+    handling: Handling,
+    // the held direction
+    shift: Option<Held>,
+    soft_drop: Option<Held>,
+}
+
+// a key that was pressed and not released yet, as far as its repeats tell
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Held {
+    action: Action,
+    // time since the press
+    since: u32,
+    // time since the last repeat, or the press
+    quiet: u32,
+    // time between the last two presses
+    gap: Option<u32>,
+    // whether the last press is a tap or a repeat is only told by the next one
+    pending: bool,
+    repeating: bool,
+    // whether repeating started in the current frame
+    started: bool,
+    // auto repeats emitted so far
+    repeated: u32,
+}
+
+impl Held {
+    fn new(action: Action) -> Self {
+        Self {
+            action,
+            since: 0,
+            quiet: 0,
+            gap: None,
+            pending: false,
+            repeating: false,
+            started: false,
+            repeated: 0,
+        }
+    }
+}
+
+impl AutoRepeat {
+    /// Applies `handling` to the actions passed to [`apply`](Self::apply).
+    pub fn new(handling: Handling) -> Self {
+        Self {
+            handling,
+            shift: None,
+            soft_drop: None,
+        }
+    }
+
+    /// The handling applied.
+    pub fn handling(&self) -> Handling {
+        self.handling
+    }
+
+    /// Turns the `actions` of the keys pressed or repeated during the last `dt` ms into the
+    /// actions to [step](crate::GameState::step) the game with. Repeats of a held key are replaced
+    /// by the moves following from the handling, soft drops repeat `soft_drop_factor` times faster
+    /// than `gravity_interval`.
+    pub fn apply(&mut self, actions: &[Action], dt: u32, gravity_interval: u32) -> Vec<Action> {
+        let mut applied = Vec::new();
+        for &action in actions {
+            let slot = match action {
+                Action::Left | Action::Right => &mut self.shift,
+                Action::SoftDrop => &mut self.soft_drop,
+                _ => {
+                    applied.push(action);
+                    continue;
+                }
+            };
+            match slot {
+                Some(held) if held.action == action => {
+                    let gap = core::mem::take(&mut held.quiet);
+                    let steady = held.gap.is_some_and(|last| gap.abs_diff(last) <= JITTER);
+                    held.gap = Some(gap);
+                    if held.repeating {
+                        continue;
+                    }
+                    if steady && gap <= RELEASE {
+                        // two presses at the same quick cadence, the key is held
+                        held.pending = false;
+                        held.started = true;
+                        held.repeating = true;
+                        continue;
+                    }
+                    // the press before was a tap after all
+                    if core::mem::take(&mut held.pending) {
+                        applied.push(action);
+                    }
+                    if gap <= RELEASE {
+                        held.pending = true;
+                    } else {
+                        // another tap, or the first repeat of a held key, which cannot be told
+                        // apart
+                        applied.push(action);
+                    }
+                }
+                _ => {
+                    *slot = Some(Held::new(action));
+                    applied.push(action);
+                }
+            }
+        }
+
+        let soft_drop_interval = (gravity_interval / self.handling.soft_drop_factor.max(1)).max(1);
+        for (slot, delay, interval) in [
+            (&mut self.shift, self.handling.das, self.handling.arr),
+            (&mut self.soft_drop, 0, soft_drop_interval),
+        ] {
+            let Some(held) = slot else {
+                continue;
+            };
+            held.since += dt;
+            held.quiet += dt;
+            if held.pending && held.gap.is_some_and(|gap| held.quiet > gap + JITTER) {
+                // no repeat followed at the same cadence, the press was a tap
+                held.pending = false;
+                applied.push(held.action);
+            }
+            let timeout = if held.repeating {
+                RELEASE
+            } else {
+                FIRST_REPEAT
+            };
+            if held.quiet > timeout {
+                *slot = None;
+                continue;
+            }
+            if !held.repeating || held.since < delay {
+                continue;
+            }
+            if interval == 0 {
+                // as far as it goes, moves into the wall change nothing
+                applied.extend([held.action; COLS]);
+                continue;
+            }
+            let due = (held.since - delay) / interval + 1;
+            if core::mem::take(&mut held.started) {
+                // the terminal may start repeating long after the delay, start from here instead
+                // of catching up at once
+                held.repeated = due - 1;
+            }
+            for _ in held.repeated..due {
+                applied.push(held.action);
+            }
+            held.repeated = held.repeated.max(due);
+        }
+        applied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a terminal repeating every 30 ms after 100 ms
+    fn hold(repeat: &mut AutoRepeat, action: Action, frames: u32) -> Vec<Action> {
+        let mut applied = repeat.apply(&[action], 0, 1000);
+        for frame in 1..=frames {
+            let repeated = frame * 10 >= 100 && frame % 3 == 0;
+            let actions = if repeated { &[action][..] } else { &[] };
+            applied.extend(repeat.apply(actions, 10, 1000));
+        }
+        applied
+    }
+
+    #[test]
+    fn taps_move_once() {
+        let mut repeat = AutoRepeat::default();
+        assert_eq!(repeat.apply(&[Action::Left], 0, 1000), [Action::Left]);
+        assert!(repeat.apply(&[], 1000, 1000).is_empty());
+        assert_eq!(
+            repeat.apply(&[Action::Left, Action::Hold], 0, 1000),
+            [Action::Left, Action::Hold]
+        );
+    }
+
+    #[test]
+    fn taps_at_a_human_pace_move_once_each() {
+        let mut repeat = AutoRepeat::default();
+        let mut applied = Vec::new();
+        // taps 200 ms apart, in 10 ms frames
+        for frame in 0..=40 {
+            let actions = if frame % 20 == 0 {
+                &[Action::Left][..]
+            } else {
+                &[]
+            };
+            applied.extend(repeat.apply(actions, 10, 1000));
+        }
+        assert_eq!(applied, [Action::Left; 3]);
+    }
+
+    #[test]
+    fn fast_double_taps_move_twice() {
+        let mut repeat = AutoRepeat::default();
+        let mut applied = Vec::new();
+        // taps 60 ms apart, in 10 ms frames
+        for frame in 0..=20 {
+            let actions = if frame % 6 == 0 && frame <= 6 {
+                &[Action::Left][..]
+            } else {
+                &[]
+            };
+            applied.extend(repeat.apply(actions, 10, 1000));
+        }
+        assert_eq!(applied, [Action::Left; 2]);
+    }
+
+    #[test]
+    fn held_directions_shift_after_the_delay() {
+        let mut repeat = AutoRepeat::new(Handling {
+            das: 200,
+            arr: 50,
+            soft_drop_factor: 20,
+        });
+        // the press, the first repeat at 120 ms, then once repeating shifts at 200, 250, 300 and
+        // 350 ms
+        let applied = hold(&mut repeat, Action::Right, 39);
+        assert_eq!(applied, [Action::Right; 6]);
+
+        // released after the repeats stop
+        assert!(repeat.apply(&[], 200, 1000).is_empty());
+        assert_eq!(repeat.apply(&[Action::Right], 0, 1000), [Action::Right]);
+    }
+
+    #[test]
+    fn no_repeat_rate_shifts_to_the_wall() {
+        let mut repeat = AutoRepeat::new(Handling {
+            das: 100,
+            arr: 0,
+            soft_drop_factor: 20,
+        });
+        let applied = hold(&mut repeat, Action::Left, 18);
+        assert!(applied.len() > COLS);
+        assert!(applied.iter().all(|action| *action == Action::Left));
+    }
+
+    #[test]
+    fn soft_drop_repeats_faster_than_gravity() {
+        let mut repeat = AutoRepeat::new(Handling {
+            das: 100,
+            arr: 50,
+            soft_drop_factor: 50,
+        });
+        // the press, the first repeat at 120 ms and once the repeats at 150 and 180 ms are steady
+        // one drop every 20 ms
+        let applied = hold(&mut repeat, Action::SoftDrop, 24);
+        assert_eq!(applied.len(), 2 + 4);
+    }
+}
@@ -1,81 +1,68 @@
-use core::fmt::{Display, Write};
+//! Decoding terminal input into [`Key`]s and mapping them to game [`Action`](crate::Action)s,
+//! see [`bindings`].
+
+use core::{
+    fmt::{Display, Write},
+    ops::BitOr,
+};
 
 use alloc::vec::Vec;
-use libtinyos::{eprintln, syscalls};
-
-pub fn query_keyboard_once(buf: &mut [u8]) -> Vec<KeyCode> {
-    unsafe { syscalls::seek(syscalls::STDIN_FILENO, 0) }.unwrap();
-    let res = unsafe { syscalls::read(syscalls::STDIN_FILENO, buf.as_mut_ptr(), buf.len(), 50) };
-    if let Ok(res) = res {
-        parse_ansi(&buf[..res as usize])
-    } else {
-        eprintln!("Syscall read failed.");
-        Vec::new()
-    }
-}
 
-fn parse_ansi(buf: &[u8]) -> Vec<KeyCode> {
-    let mut codes = Vec::new();
-    let mut cursor = 0;
-    while let Some(current) = buf.get(cursor) {
-        #[allow(clippy::single_match)]
-        match *current {
-            0x1B => codes.push(parse_escaped(buf, &mut cursor)),
-            _ => {
-                codes.push(
-                    str::from_utf8(&buf[cursor..=cursor])
-                        .map(|s| KeyCode::Char(s.chars().next().unwrap_or('?')))
-                        .unwrap_or(KeyCode::Unknown),
-                );
-                cursor += 1;
-            }
-        }
-    }
-    codes
-}
+pub mod bindings;
+pub mod decode;
+pub mod handling;
+pub mod pacing;
+pub mod source;
 
-fn parse_escaped(buf: &[u8], cursor: &mut usize) -> KeyCode {
-    // for now we assume only arrows or a single esc
-    match buf.get(*cursor + 1) {
-        None => {
-            *cursor += 1;
-            KeyCode::Esc
-        }
-        Some(byte) if *byte == b'[' => match buf.get(*cursor + 2) {
-            None => {
-                *cursor += 1;
-                KeyCode::Esc
-            }
-            Some(byte) => {
-                *cursor += 3;
-
-                match byte {
-                    b'A' => KeyCode::ArrowUp,
-                    b'D' => KeyCode::ArrowLeft,
-                    b'B' => KeyCode::ArrowDown,
-                    b'C' => KeyCode::ArrowRight,
-                    _ => {
-                        *cursor -= 2;
-                        KeyCode::Esc
-                    }
-                }
-            }
-        },
-        _ => {
-            *cursor += 1;
-            KeyCode::Esc
-        }
-    }
+use decode::Decoder;
+
+/// Decodes the raw bytes of one complete read from a terminal into the keys they encode.
+///
+/// Sequences cut off at the end of `buf` are decoded as far as they go. Use a [`Decoder`] to
+/// decode input arriving in several reads.
+pub fn parse_ansi(buf: &[u8]) -> Vec<Key> {
+    let mut decoder = Decoder::default();
+    let mut keys = decoder.decode(buf);
+    keys.extend(decoder.flush());
+    keys
 }
 
+/// A key pressed on the terminal.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum KeyCode {
+    /// The up arrow.
     ArrowUp,
+    /// The down arrow.
     ArrowDown,
+    /// The left arrow.
     ArrowLeft,
+    /// The right arrow.
     ArrowRight,
+    /// The home key.
+    Home,
+    /// The end key.
+    End,
+    /// The page up key.
+    PageUp,
+    /// The page down key.
+    PageDown,
+    /// The insert key.
+    Insert,
+    /// The delete key, not backspace.
+    Delete,
+    /// One of the function keys F1 to F12.
+    F(u8),
+    /// The tab key.
+    Tab,
+    /// The enter or return key.
+    Enter,
+    /// The backspace key.
+    Backspace,
+    /// A printable character.
     Char(char),
+    /// The escape key on its own.
     Esc,
+    /// Anything that could not be decoded.
     Unknown,
 }
 
@@ -86,9 +73,161 @@ impl Display for KeyCode {
             Self::ArrowDown => f.write_str("Down"),
             Self::ArrowLeft => f.write_str("Left"),
             Self::ArrowRight => f.write_str("Right"),
+            Self::Home => f.write_str("Home"),
+            Self::End => f.write_str("End"),
+            Self::PageUp => f.write_str("PageUp"),
+            Self::PageDown => f.write_str("PageDown"),
+            Self::Insert => f.write_str("Insert"),
+            Self::Delete => f.write_str("Delete"),
+            Self::F(n) => write!(f, "F{n}"),
+            Self::Tab => f.write_str("Tab"),
+            Self::Enter => f.write_str("Enter"),
+            Self::Backspace => f.write_str("Backspace"),
+            // spelled out to keep them apart from the separators of key files
+            Self::Char(' ') => f.write_str("Space"),
+            Self::Char(',') => f.write_str("Comma"),
             Self::Char(c) => f.write_char(*c),
             Self::Esc => f.write_str("Esc"),
             Self::Unknown => f.write_str("Unknown"),
         }
     }
 }
+
+impl KeyCode {
+    /// The key called `name`, the inverse of displaying a key.
+    pub fn named(name: &str) -> Option<Self> {
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Some(Self::Char(c));
+        }
+        Some(match name {
+            "Up" => Self::ArrowUp,
+            "Down" => Self::ArrowDown,
+            "Left" => Self::ArrowLeft,
+            "Right" => Self::ArrowRight,
+            "Home" => Self::Home,
+            "End" => Self::End,
+            "PageUp" => Self::PageUp,
+            "PageDown" => Self::PageDown,
+            "Insert" => Self::Insert,
+            "Delete" => Self::Delete,
+            "Tab" => Self::Tab,
+            "Enter" => Self::Enter,
+            "Backspace" => Self::Backspace,
+            "Space" => Self::Char(' '),
+            "Comma" => Self::Char(','),
+            "Esc" => Self::Esc,
+            "Unknown" => Self::Unknown,
+            _ => {
+                let n = name.strip_prefix('F')?.parse().ok()?;
+                if !(1..=12).contains(&n) {
+                    return None;
+                }
+                Self::F(n)
+            }
+        })
+    }
+}
+
+/// The modifier keys held while pressing a key, as far as the terminal reports them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    /// No modifiers.
+    pub const NONE: Self = Self(0);
+    /// Shift.
+    pub const SHIFT: Self = Self(1);
+    /// Alt, or meta on some terminals.
+    pub const ALT: Self = Self(2);
+    /// Control.
+    pub const CTRL: Self = Self(4);
+
+    /// Whether all of `other` are held.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether no modifiers are held.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for Modifiers {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// A [`KeyCode`] together with the [`Modifiers`] held while pressing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    /// The key pressed.
+    pub code: KeyCode,
+    /// The modifiers held.
+    pub modifiers: Modifiers,
+}
+
+impl Key {
+    /// `code` pressed while holding `modifiers`.
+    pub const fn new(code: KeyCode, modifiers: Modifiers) -> Self {
+        Self { code, modifiers }
+    }
+}
+
+impl Key {
+    /// The key called `name`, e.g. `Ctrl+Left` or `x`, the inverse of displaying a key.
+    pub fn named(name: &str) -> Option<Self> {
+        let mut name = name;
+        let mut modifiers = Modifiers::NONE;
+        // a lone `+` is a key, not a modifier separator
+        while let Some((modifier, rest)) = name.split_once('+').filter(|(_, rest)| !rest.is_empty())
+        {
+            modifiers = modifiers
+                | match modifier {
+                    "Ctrl" => Modifiers::CTRL,
+                    "Alt" => Modifiers::ALT,
+                    "Shift" => Modifiers::SHIFT,
+                    _ => return None,
+                };
+            name = rest;
+        }
+        KeyCode::named(name).map(|code| Self::new(code, modifiers))
+    }
+}
+
+impl From<KeyCode> for Key {
+    fn from(code: KeyCode) -> Self {
+        Self::new(code, Modifiers::NONE)
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (modifier, name) in [
+            (Modifiers::CTRL, "Ctrl+"),
+            (Modifiers::ALT, "Alt+"),
+            (Modifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        self.code.fmt(f)
+    }
+}
+
+/// The key saving a [screenshot](crate::render::screenshot) of the current frame.
+pub const SCREENSHOT: Key = Key::new(KeyCode::Char('s'), Modifiers::NONE);
+
+/// The key opening the [rebinding screen](bindings::Rebinding).
+pub const REBIND: Key = Key::new(KeyCode::Char('b'), Modifiers::NONE);
+
+/// The key quitting the game, on frontends that can be quit.
+pub const QUIT: Key = Key::new(KeyCode::Char('q'), Modifiers::NONE);
+
+/// The keys frontends handle themselves, which can therefore not be [bound](bindings) to actions.
+pub const COMMANDS: [Key; 3] = [SCREENSHOT, REBIND, QUIT];
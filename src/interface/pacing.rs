@@ -0,0 +1,81 @@
+//! Running a game loop at a steady frame rate.
+//!
+//! A [`Pacer`] hands input pollers the deadline of the current frame, so they wait for keys no
+//! longer than the frame lasts and end it on time whether or not keys arrived. All times are
+//! milliseconds on a monotonic clock of the frontend.
+
+/// The deadline of the current frame and the time the previous one ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pacer {
+    frame: u32,
+    deadline: u64,
+    last: u64,
+}
+
+impl Pacer {
+    /// Starts a frame lasting `frame` milliseconds at `now`.
+    pub const fn new(frame: u32, now: u64) -> Self {
+        Self {
+            frame,
+            deadline: now + frame as u64,
+            last: now,
+        }
+    }
+
+    /// How long the current frame lasts.
+    pub const fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// The milliseconds left until the current frame ends, 0 once it is over.
+    pub fn remaining(&self, now: u64) -> u32 {
+        self.deadline.saturating_sub(now) as u32
+    }
+
+    /// Whether the current frame is over at `now`.
+    pub fn is_due(&self, now: u64) -> bool {
+        now >= self.deadline
+    }
+
+    /// Ends the current frame at `now` and returns the milliseconds since the previous one ended.
+    ///
+    /// The next frame ends a frame after the current deadline, keeping the rate steady when a frame
+    /// ran a little late. A loop that fell behind by more than a frame skips the missed deadlines
+    /// instead of rushing through them.
+    pub fn finish(&mut self, now: u64) -> u32 {
+        let dt = now.saturating_sub(self.last);
+        self.last = now;
+        self.deadline += u64::from(self.frame);
+        if self.deadline <= now {
+            self.deadline = now + u64::from(self.frame);
+        }
+        dt.min(u32::MAX.into()) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_end_on_the_deadline() {
+        let mut pacer = Pacer::new(16, 1000);
+        assert_eq!(pacer.remaining(1000), 16);
+        assert_eq!(pacer.remaining(1010), 6);
+        assert!(!pacer.is_due(1015));
+        assert!(pacer.is_due(1016));
+        assert_eq!(pacer.finish(1016), 16);
+        // a late frame shortens the next one
+        assert_eq!(pacer.remaining(1034), 0);
+        assert_eq!(pacer.finish(1034), 18);
+        assert_eq!(pacer.remaining(1034), 14);
+    }
+
+    #[test]
+    fn falling_behind_skips_deadlines() {
+        let mut pacer = Pacer::new(16, 0);
+        assert_eq!(pacer.finish(100), 100);
+        assert_eq!(pacer.remaining(100), 16);
+        assert_eq!(pacer.finish(116), 16);
+    }
+}
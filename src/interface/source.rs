@@ -0,0 +1,398 @@
+//! Where keys come from: a terminal, a replay file or a script.
+//!
+//! An [`InputSource`] hands out the input of the game one [`Frame`] at a time, the keys typed and
+//! the time passed, so a frontend runs the same loop whichever source it reads from.
+//!
+//! Replay files start with the [`Setup`] of the game, the settings deciding how it plays out, in
+//! `setting = value` lines with the keys of every action as in key files. An empty line ends them,
+//! followed by a line per frame holding its milliseconds and keys, as written by a [`Recorder`]:
+//!
+//! ```text
+//! seed = 42
+//! das = 170
+//! arr = 50
+//! sdf = 20
+//! animations = true
+//! left = Left
+//! ...
+//! pause = p, Esc
+//!
+//! 16
+//! 16 Left, Left
+//! 0 Space
+//! ```
+
+use alloc::{
+    collections::VecDeque,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use super::{
+    Key,
+    bindings::{ACTIONS, Bindings, BindingsError},
+    handling::Handling,
+};
+use crate::pairs::pairs;
+
+/// The input of one frame of the game.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// The keys typed, in order.
+    pub keys: Vec<Key>,
+    /// Milliseconds passed since the last frame.
+    pub dt: u32,
+}
+
+/// The ways reading input can fail, with what the platform reported.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InputError {
+    /// The device behind the source could not be set up, e.g. a terminal refusing raw mode.
+    #[error("opening the input failed: {0}")]
+    Open(String),
+    /// The device or file behind the source could not be read.
+    #[error("reading the input failed: {0}")]
+    Read(String),
+    /// The device behind the source could not be rewound to the start of its input.
+    #[error("rewinding the input failed: {0}")]
+    Seek(String),
+    /// The clock pacing the frames could not be read.
+    #[error("reading the clock failed: {0}")]
+    Clock(String),
+}
+
+/// Something handing out the input of the game frame by frame.
+pub trait InputSource {
+    /// The input of the next frame, `None` once the source has ended.
+    fn next_frame(&mut self) -> Result<Option<Frame>, InputError>;
+}
+
+impl<S: InputSource + ?Sized> InputSource for alloc::boxed::Box<S> {
+    fn next_frame(&mut self) -> Result<Option<Frame>, InputError> {
+        (**self).next_frame()
+    }
+}
+
+/// A fixed sequence of frames, e.g. for tests.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Scripted {
+    frames: VecDeque<Frame>,
+}
+
+impl Scripted {
+    /// Hands out `frames` in order.
+    pub fn new(frames: impl IntoIterator<Item = Frame>) -> Self {
+        Self {
+            frames: frames.into_iter().collect(),
+        }
+    }
+}
+
+impl InputSource for Scripted {
+    fn next_frame(&mut self) -> Result<Option<Frame>, InputError> {
+        Ok(self.frames.pop_front())
+    }
+}
+
+/// Everything besides its input that decides how a game plays out, recorded in replay files so
+/// the replay plays out the same game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setup {
+    /// The seed the game is started with.
+    pub seed: u64,
+    /// The keys when the game starts, changing them while playing is part of the input.
+    pub bindings: Bindings,
+    /// How held keys move the falling piece.
+    pub handling: Handling,
+    /// Whether [animations](crate::render::Appearance::animations) delay line clears and
+    /// restarts.
+    pub animations: bool,
+}
+
+impl Setup {
+    /// The default settings for a game started with `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            bindings: Bindings::default(),
+            handling: Handling::default(),
+            animations: true,
+        }
+    }
+
+    // the settings as the header of a replay file, ending with its empty line
+    fn header(&self) -> String {
+        let Handling {
+            das,
+            arr,
+            soft_drop_factor,
+        } = self.handling;
+        let mut header = format!(
+            "seed = {}\ndas = {das}\narr = {arr}\nsdf = {soft_drop_factor}\nanimations = {}\n",
+            self.seed, self.animations
+        );
+        for (name, action) in ACTIONS {
+            header.push_str(&format!("{name} = {}\n", self.bindings.describe(action)));
+        }
+        header.push('\n');
+        header
+    }
+}
+
+/// The ways a replay file can be wrong, each with the number of the offending line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum ReplayError {
+    /// The settings do not hold the seed.
+    #[error("missing `seed = <number>`")]
+    MissingSeed,
+    /// A line of the settings is no `setting = value` pair.
+    #[error("line {0}: expected `setting = value`")]
+    Setting(usize),
+    /// A setting has a value it does not take.
+    #[error("line {0}: invalid value")]
+    InvalidValue(usize),
+    /// The keys of an action are invalid.
+    #[error(transparent)]
+    Bindings(#[from] BindingsError),
+    /// A line does not start with the milliseconds of its frame.
+    #[error("line {0}: expected `<milliseconds> <keys>`")]
+    Syntax(usize),
+    /// A key name could not be read.
+    #[error("line {0}: unknown key")]
+    UnknownKey(usize),
+}
+
+/// A recorded game, played back frame by frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    setup: Setup,
+    frames: Scripted,
+}
+
+impl Replay {
+    /// Reads a replay file.
+    pub fn parse(text: &str) -> Result<Self, ReplayError> {
+        // the settings end with the first empty line
+        let settings = text
+            .lines()
+            .position(|line| line.trim().is_empty())
+            .unwrap_or(usize::MAX);
+        let mut setup = Setup::new(0);
+        let mut seeded = false;
+        for pair in pairs(text.lines().take(settings)) {
+            let (number, key, value) = pair.map_err(ReplayError::Setting)?;
+            let handling = &mut setup.handling;
+            let setting = match key {
+                "seed" => {
+                    seeded = true;
+                    value.parse().map(|seed| setup.seed = seed).is_ok()
+                }
+                "das" => value.parse().map(|das| handling.das = das).is_ok(),
+                "arr" => value.parse().map(|arr| handling.arr = arr).is_ok(),
+                "sdf" => value
+                    .parse()
+                    .map(|sdf| handling.soft_drop_factor = sdf)
+                    .is_ok(),
+                "animations" => value.parse().map(|on| setup.animations = on).is_ok(),
+                _ => {
+                    setup.bindings.set(key, value, number)?;
+                    true
+                }
+            };
+            if !setting {
+                return Err(ReplayError::InvalidValue(number));
+            }
+        }
+        if !seeded {
+            return Err(ReplayError::MissingSeed);
+        }
+
+        let mut frames = Vec::new();
+        for (idx, line) in text.lines().enumerate().skip(settings.saturating_add(1)) {
+            let number = idx + 1;
+            let line = line.trim();
+            let (dt, keys) = line.split_once(' ').unwrap_or((line, ""));
+            let dt = dt.parse().map_err(|_| ReplayError::Syntax(number))?;
+            let keys = keys
+                .split(',')
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(|key| Key::named(key).ok_or(ReplayError::UnknownKey(number)))
+                .collect::<Result<_, _>>()?;
+            frames.push(Frame { keys, dt });
+        }
+        Ok(Self {
+            setup,
+            frames: Scripted::new(frames),
+        })
+    }
+
+    /// The settings the recorded game was started with.
+    pub fn setup(&self) -> &Setup {
+        &self.setup
+    }
+}
+
+impl InputSource for Replay {
+    fn next_frame(&mut self) -> Result<Option<Frame>, InputError> {
+        self.frames.next_frame()
+    }
+}
+
+/// Passes on the frames of another source, writing them down as a replay file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recorder<S> {
+    source: S,
+    file: String,
+}
+
+impl<S: InputSource> Recorder<S> {
+    /// Records the frames of `source`, for a game started with `setup`.
+    pub fn new(source: S, setup: &Setup) -> Self {
+        Self {
+            source,
+            file: setup.header(),
+        }
+    }
+
+    /// The replay file of everything recorded so far, [`Replay::parse`] reads it back.
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+}
+
+impl<S: InputSource> InputSource for Recorder<S> {
+    fn next_frame(&mut self) -> Result<Option<Frame>, InputError> {
+        let frame = self.source.next_frame()?;
+        if let Some(frame) = &frame {
+            self.file.push_str(&frame.dt.to_string());
+            for (idx, key) in frame.keys.iter().enumerate() {
+                self.file.push_str(if idx == 0 { " " } else { ", " });
+                self.file.push_str(&key.to_string());
+            }
+            self.file.push('\n');
+        }
+        Ok(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Action, GameState,
+        interface::{KeyCode, Modifiers, handling::AutoRepeat},
+    };
+
+    fn frames() -> Vec<Frame> {
+        Vec::from([
+            Frame {
+                keys: Vec::new(),
+                dt: 16,
+            },
+            Frame {
+                keys: Vec::from([
+                    KeyCode::ArrowLeft.into(),
+                    KeyCode::Char(',').into(),
+                    Key::new(KeyCode::Char('c'), Modifiers::CTRL),
+                    KeyCode::Unknown.into(),
+                ]),
+                dt: 0,
+            },
+        ])
+    }
+
+    // plays the frames of `input` like a frontend set up with `setup` would
+    fn play(setup: &Setup, input: &mut dyn InputSource) -> GameState {
+        let mut state = GameState::new(setup.seed);
+        if setup.animations {
+            state.set_line_clear_delay(300);
+        }
+        let mut repeat = AutoRepeat::new(setup.handling);
+        while let Some(Frame { keys, dt }) = input.next_frame().unwrap() {
+            let actions: Vec<_> = keys
+                .iter()
+                .filter_map(|key| setup.bindings.action(key))
+                .collect();
+            let actions = repeat.apply(&actions, dt, state.gravity_interval());
+            state.step(&actions, dt);
+        }
+        state
+    }
+
+    #[test]
+    fn recordings_replay_the_same_frames() {
+        let mut recorder = Recorder::new(Scripted::new(frames()), &Setup::new(7));
+        while recorder.next_frame().unwrap().is_some() {}
+        assert!(recorder.file().starts_with("seed = 7\ndas = 170\n"));
+        assert!(recorder.file().contains("\npause = p, Esc\n\n"));
+        assert!(
+            recorder
+                .file()
+                .ends_with("\n\n16\n0 Left, Comma, Ctrl+c, Unknown\n")
+        );
+
+        let mut replay = Replay::parse(recorder.file()).unwrap();
+        assert_eq!(replay.setup(), &Setup::new(7));
+        for frame in frames() {
+            assert_eq!(replay.next_frame(), Ok(Some(frame)));
+        }
+        assert_eq!(replay.next_frame(), Ok(None));
+    }
+
+    #[test]
+    fn replays_play_with_the_recorded_settings() {
+        let mut setup = Setup::new(3);
+        setup.handling.das = 60;
+        setup.animations = false;
+        setup.bindings.bind(KeyCode::Char('a').into(), Action::Left);
+        // `a` held down, repeated by the terminal every 30 ms after 100 ms
+        let held = (0..40).map(|frame| Frame {
+            keys: if frame == 0 || (frame >= 10 && frame % 3 == 0) {
+                Vec::from([KeyCode::Char('a').into()])
+            } else {
+                Vec::new()
+            },
+            dt: 10,
+        });
+        let mut recorder = Recorder::new(Scripted::new(held.clone()), &setup);
+        let recorded = play(&setup, &mut recorder);
+
+        let mut replay = Replay::parse(recorder.file()).unwrap();
+        assert_eq!(replay.setup().handling, setup.handling);
+        assert_eq!(replay.setup().bindings.to_file(), setup.bindings.to_file());
+        let replayed = play(&replay.setup().clone(), &mut replay);
+        assert_eq!(replayed, recorded);
+        // the default settings play out a different game
+        assert_ne!(play(&Setup::new(3), &mut Scripted::new(held)), recorded);
+    }
+
+    #[test]
+    fn broken_replay_files_are_rejected() {
+        assert_eq!(
+            Replay::parse("das = 10\n\n16"),
+            Err(ReplayError::MissingSeed)
+        );
+        assert_eq!(
+            Replay::parse("seed = 1\nseed 2"),
+            Err(ReplayError::Setting(2))
+        );
+        assert_eq!(
+            Replay::parse("seed = 1\nanimations = no"),
+            Err(ReplayError::InvalidValue(2))
+        );
+        assert_eq!(
+            Replay::parse("seed = 1\nspin = x"),
+            Err(ReplayError::Bindings(BindingsError::UnknownAction(2)))
+        );
+        assert_eq!(
+            Replay::parse("seed = 1\n\n16\nLeft"),
+            Err(ReplayError::Syntax(4))
+        );
+        assert_eq!(
+            Replay::parse("seed = 1\n\n16 Leftt"),
+            Err(ReplayError::UnknownKey(3))
+        );
+    }
+}
@@ -0,0 +1,37 @@
+//! The rules of tinyTetris as a `no_std` library.
+//!
+//! [`GameState`] holds a running game and advances it through [`GameState::step`], which takes the
+//! player's [`Action`]s and the elapsed time and reports what happened as [`Event`]s, which can be
+//! handed to any number of [`Subscriber`]s. The game rules never draw or read input. [`interface`]
+//! decodes terminal input and [`render`] draws frames into any text sink or pixel target, but the
+//! actual I/O is left to a frontend such as the tinyOS binary, which reports failures as an
+//! [`Error`].
+//!
+//! ```
+//! use tiny_tetris::{Action, Event, GameState};
+//!
+//! let mut game = GameState::new(42);
+//! let events = game.step(&[Action::Left, Action::RotateClockwise], 16);
+//! assert!(!events.contains(&Event::GameOver));
+//! ```
+
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+#![warn(missing_docs)]
+
+extern crate alloc;
+
+pub use embedded_graphics;
+
+pub mod crash;
+pub mod error;
+pub mod game;
+pub mod interface;
+mod pairs;
+pub mod render;
+
+pub use error::Error;
+pub use game::{
+    Action, COLS, GameState, LINES,
+    event::{Event, Subscriber},
+};
+pub use render::Renderer;
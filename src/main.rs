@@ -3,18 +3,27 @@
 
 extern crate alloc;
 
-use libtinyos::{println, process::ProcessError};
+use libtinyos::{eprintln, println, process::ProcessError};
+use tiny_tetris::Error;
 
-use crate::{game::game_loop, graphics::init_gfx};
+use crate::{
+    frontend::{Stdin, game_loop, install_crash_hook},
+    graphics::init_gfx,
+};
 
-mod game;
+mod frontend;
 mod graphics;
-mod interface;
 
 #[unsafe(no_mangle)]
 pub fn main() -> Result<(), ProcessError> {
     println!("Welcome to TinyTetris.\nLaunching the game...");
+    install_crash_hook();
     init_gfx();
-    game_loop();
-    Ok(())
+    Stdin::new()
+        .map_err(Error::from)
+        .and_then(game_loop)
+        .map_err(|err| {
+            eprintln!("tinyTetris stopped: {err}");
+            ProcessError
+        })
 }
@@ -0,0 +1,37 @@
+//! The line format shared by key, theme, settings and replay files: one `key = value` pair per
+//! line, skipping empty lines and comments starting with `#`.
+
+/// The pairs in `lines` as `(line, key, value)`, numbered from 1 and trimmed. A line that is no
+/// pair is an error with its number.
+pub(crate) fn pairs<'a>(
+    lines: impl IntoIterator<Item = &'a str>,
+) -> impl Iterator<Item = Result<(usize, &'a str, &'a str), usize>> {
+    lines.into_iter().enumerate().filter_map(|(idx, line)| {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let number = idx + 1;
+        let pair = line.split_once('=').ok_or(number);
+        Some(pair.map(|(key, value)| (number, key.trim(), value.trim())))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn comments_and_empty_lines_are_skipped() {
+        let text = "# colors\n\n base= classic \nlong = #00ffff = x\ngrid";
+        assert_eq!(
+            pairs(text.lines()).collect::<Vec<_>>(),
+            [
+                Ok((3, "base", "classic")),
+                Ok((4, "long", "#00ffff = x")),
+                Err(5)
+            ]
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! Short animations on top of the board: flashing and collapsing completed lines, flashing
+//! locked pieces and filling the board on game over.
+//!
+//! Animations run on the [game clock](GameState::clock) and only change what cells look like,
+//! the game itself never waits for them. Only the line-clear delay of the game decides how long
+//! completed lines stay on the board.
+
+use crate::{COLS, Event, GameState, LINES, Subscriber, game::piece::Piece};
+
+use super::damage::{Cells, Content};
+
+/// How long a locked piece flashes, in milliseconds.
+pub const LOCK_FLASH: u32 = 100;
+/// How long the board takes to fill up on game over, in milliseconds.
+pub const TOP_OUT: u32 = 1000;
+/// A line-clear delay fitting the line-clear animation, in milliseconds.
+pub const LINE_CLEAR: u32 = 300;
+// completed lines toggle between lit and normal this often while flashing
+const FLASH_PERIOD: u32 = 50;
+
+/// The animations currently running, learning about new ones from the game's [`Event`]s.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Animations {
+    // the last locked piece and when its flash started, once known
+    lock: Option<(Piece, Option<u32>)>,
+    // when the top out started, once known
+    top_out: Option<Option<u32>>,
+}
+
+impl Animations {
+    /// Changes `cells`, showing `state`, to how they look at the current [`GameState::clock`].
+    pub fn apply(&mut self, state: &GameState, cells: &mut Cells) {
+        let now = state.clock();
+
+        if let Some(clearing) = state.clearing() {
+            let delay = state.line_clear_delay().max(1);
+            let half = delay / 2;
+            let elapsed = clearing.elapsed();
+            for &line in clearing.lines() {
+                let row = &mut cells[line];
+                if elapsed < half {
+                    if (elapsed / FLASH_PERIOD) % 2 == 0 {
+                        *row = [Content::Flash; COLS];
+                    }
+                } else {
+                    // empty from the center outwards
+                    let gone =
+                        ((elapsed - half) * COLS as u32 / 2 / (delay - half).max(1)) as usize + 1;
+                    let center = COLS / 2;
+                    for cell in &mut row[center.saturating_sub(gone)..(center + gone).min(COLS)] {
+                        *cell = Content::Empty;
+                    }
+                }
+            }
+        }
+
+        if let Some((piece, start)) = &mut self.lock {
+            let start = *start.get_or_insert(now);
+            if now.saturating_sub(start) < LOCK_FLASH {
+                for cell in piece.cells() {
+                    let content = usize::try_from(cell.row)
+                        .ok()
+                        .zip(usize::try_from(cell.col).ok())
+                        .and_then(|(row, col)| cells.get_mut(row)?.get_mut(col));
+                    // cleared lines no longer hold the piece
+                    if let Some(content @ Content::Block(_)) = content {
+                        *content = Content::Flash;
+                    }
+                }
+            } else {
+                self.lock = None;
+            }
+        }
+
+        if !state.is_over() {
+            self.top_out = None;
+        }
+        if let Some(start) = &mut self.top_out {
+            let start = *start.get_or_insert(now);
+            let rows = (now.saturating_sub(start) * LINES as u32 / TOP_OUT) as usize;
+            for row in &mut cells[LINES - rows.min(LINES)..] {
+                *row = [Content::Filled; COLS];
+            }
+        }
+    }
+}
+
+impl Subscriber for Animations {
+    fn notify(&mut self, event: &Event) {
+        match event {
+            Event::PieceLocked(piece) => self.lock = Some((piece.clone(), None)),
+            Event::GameOver => self.top_out = Some(None),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, render::damage};
+
+    // steps `state`, passing every event to `animations`, and returns whether `until` happened
+    fn step(
+        state: &mut GameState,
+        animations: &mut Animations,
+        actions: &[Action],
+        dt: u32,
+        until: fn(&Event) -> bool,
+    ) -> bool {
+        let events = state.step(actions, dt);
+        events.iter().for_each(|event| animations.notify(event));
+        events.iter().any(until)
+    }
+
+    fn shown(state: &GameState, animations: &mut Animations) -> Cells {
+        let mut cells = damage::cells(state);
+        animations.apply(state, &mut cells);
+        cells
+    }
+
+    #[test]
+    fn locked_pieces_flash_briefly() {
+        let mut state = GameState::new(0);
+        let mut animations = Animations::default();
+        let locked = |event: &Event| matches!(event, Event::PieceLocked(_));
+        while !step(&mut state, &mut animations, &[Action::SoftDrop], 0, locked) {}
+        let piece = animations.lock.clone().unwrap().0;
+
+        let cells = shown(&state, &mut animations);
+        for cell in piece.cells() {
+            assert_eq!(cells[cell.row as usize][cell.col as usize], Content::Flash);
+        }
+
+        state.step(&[], LOCK_FLASH);
+        let cells = shown(&state, &mut animations);
+        for cell in piece.cells() {
+            assert_eq!(
+                cells[cell.row as usize][cell.col as usize],
+                Content::Block(piece.kind())
+            );
+        }
+    }
+
+    #[test]
+    fn the_board_fills_up_after_a_top_out() {
+        let mut state = GameState::new(0);
+        let mut animations = Animations::default();
+        let over = |event: &Event| *event == Event::GameOver;
+        while !step(&mut state, &mut animations, &[Action::SoftDrop], 0, over) {}
+
+        let filled = |cells: &Cells| {
+            cells
+                .iter()
+                .filter(|row| **row == [Content::Filled; COLS])
+                .count()
+        };
+        assert_eq!(filled(&shown(&state, &mut animations)), 0);
+        state.step(&[], TOP_OUT / 2);
+        let cells = shown(&state, &mut animations);
+        assert_eq!(filled(&cells), LINES / 2);
+        assert_eq!(cells[LINES - 1], [Content::Filled; COLS]);
+        state.step(&[], TOP_OUT / 2);
+        assert_eq!(filled(&shown(&state, &mut animations)), LINES);
+
+        state.restart();
+        assert_eq!(filled(&shown(&state, &mut animations)), 0);
+    }
+}
@@ -0,0 +1,333 @@
+//! Drawing the game as text, using ANSI escape sequences for colors and cursor movement.
+
+use core::fmt::{self, Display, Formatter, Result, Write};
+
+use alloc::{string::String, vec::Vec};
+use embedded_graphics::{pixelcolor::Rgb888, prelude::RgbColor};
+
+use super::{
+    Appearance, RenderError, Renderer,
+    animation::Animations,
+    damage::{self, Cells, Content},
+    hud,
+    pattern::Pattern,
+    screenshot::Screenshot,
+    style::{BlockStyle, blend},
+    theme::Theme,
+};
+use crate::{COLS, Event, GameState, Subscriber, game::piece::PieceKind};
+
+const RESET: &str = "\x1b[0m";
+
+/// A [`Renderer`] writing frames as text into `W`, e.g. a terminal. Every cell is two characters
+/// wide, so it comes out roughly square. Colors are written as 24 bit colors, which most terminals
+/// support.
+///
+/// Each frame is composed first and then written in one go, so the terminal never shows half a
+/// frame.
+pub struct AnsiRenderer<W> {
+    out: W,
+    appearance: Appearance,
+    frame: String,
+    entered: bool,
+    animations: Animations,
+    // the lines shown by the last menu, if it is still shown
+    menu: Option<Vec<String>>,
+}
+
+impl<W: Write> AnsiRenderer<W> {
+    /// Creates a renderer writing into `out`. The terminal is cleared with the first frame.
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            appearance: Appearance::default(),
+            frame: String::new(),
+            entered: false,
+            animations: Animations::default(),
+            menu: None,
+        }
+    }
+
+    /// The writer frames go to.
+    pub fn writer(&self) -> &W {
+        &self.out
+    }
+}
+
+impl<W: Write> Renderer for AnsiRenderer<W> {
+    fn draw_frame(&mut self, state: &GameState) -> core::result::Result<(), RenderError> {
+        self.frame.clear();
+        // the menu may be wider than the board, so clear the terminal again
+        if !self.entered || self.menu.take().is_some() {
+            enter(&mut self.frame)?;
+            self.entered = true;
+        }
+        let mut cells = damage::cells(state);
+        if self.appearance.animations {
+            self.animations.apply(state, &mut cells);
+        }
+        draw(state, &cells, &self.appearance, &mut self.frame)?;
+        self.out.write_str(&self.frame)?;
+        Ok(())
+    }
+
+    fn draw_menu(&mut self, lines: &[String]) -> core::result::Result<(), RenderError> {
+        if self.menu.as_deref() == Some(lines) {
+            return Ok(());
+        }
+        self.frame.clear();
+        enter(&mut self.frame)?;
+        self.entered = true;
+        self.frame.push_str("\x1b[H");
+        for (idx, line) in lines.iter().enumerate() {
+            let color = if idx == 0 {
+                self.appearance.theme.label
+            } else {
+                self.appearance.theme.text
+            };
+            write!(self.frame, "{}{line}{RESET}\r\n", Foreground(color))?;
+        }
+        self.out.write_str(&self.frame)?;
+        self.menu = Some(lines.to_vec());
+        Ok(())
+    }
+
+    fn screenshot(&self) -> Option<Screenshot<'_>> {
+        (!self.frame.is_empty()).then_some(Screenshot::Text(&self.frame))
+    }
+
+    fn set_appearance(&mut self, appearance: Appearance) {
+        self.appearance = appearance;
+    }
+
+    fn invalidate(&mut self) {
+        // whatever was written over the frame is cleared with the next one
+        self.entered = false;
+        self.menu = None;
+    }
+
+    fn finish(&mut self) -> core::result::Result<(), RenderError> {
+        if self.entered {
+            leave(&mut self.out)?;
+            self.entered = false;
+        }
+        Ok(())
+    }
+}
+
+impl<W> Subscriber for AnsiRenderer<W> {
+    fn notify(&mut self, event: &Event) {
+        self.animations.notify(event);
+    }
+}
+
+// clears the terminal and hides the cursor
+fn enter(out: &mut impl Write) -> Result {
+    out.write_str("\x1b[2J\x1b[?25l")
+}
+
+// resets colors and shows the cursor again
+fn leave(out: &mut impl Write) -> Result {
+    out.write_str("\x1b[0m\x1b[?25h\r\n")
+}
+
+// draws the board showing `cells` and the HUD at the top left of the terminal
+fn draw(state: &GameState, cells: &Cells, appearance: &Appearance, out: &mut impl Write) -> Result {
+    let theme = &appearance.theme;
+    out.write_str("\x1b[H")?;
+    border_line(theme, out)?;
+    for line in cells {
+        write!(out, "{}  ", Background(theme.border))?;
+        for &content in line {
+            let color = match content {
+                Content::Empty => theme.background,
+                Content::Block(kind) => theme.piece(kind),
+                Content::Ghost(_) => theme.ghost,
+                Content::Flash => theme.text,
+                Content::Filled => theme.border,
+            };
+            // text cannot bevel or outline, but it can show patterns, retro blocks and the grid
+            let (glyphs, text) = match content {
+                Content::Block(kind) if appearance.patterns => {
+                    (Pattern::of(kind).glyphs(), theme.background)
+                }
+                Content::Block(_) if appearance.block_style == BlockStyle::Retro => {
+                    ("[]", theme.background)
+                }
+                Content::Empty if appearance.grid => {
+                    (" .", blend(theme.background, theme.border, 2))
+                }
+                _ => ("  ", theme.background),
+            };
+            write!(out, "{}{}{glyphs}", Background(color), Foreground(text))?;
+        }
+        write!(out, "{}  {RESET}\r\n", Background(theme.border))?;
+    }
+    border_line(theme, out)?;
+
+    for entry in hud::entries(state) {
+        write!(
+            out,
+            "{}{}: {}{}  ",
+            Foreground(theme.label),
+            entry.label,
+            Foreground(theme.text),
+            entry.value
+        )?;
+    }
+    write!(
+        out,
+        "{}hold: {}{}{RESET}\x1b[K\r\n",
+        Foreground(theme.label),
+        Foreground(theme.text),
+        state.held_piece().map(name_of).unwrap_or("-")
+    )
+}
+
+fn border_line(theme: &Theme, out: &mut impl Write) -> Result {
+    write!(out, "{}", Background(theme.border))?;
+    for _ in 0..COLS + 2 {
+        out.write_str("  ")?;
+    }
+    write!(out, "{RESET}\r\n")
+}
+
+// the escape sequence switching the background to a 24 bit color
+struct Background(Rgb888);
+
+impl Display for Background {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "\x1b[48;2;{};{};{}m", self.0.r(), self.0.g(), self.0.b())
+    }
+}
+
+// the escape sequence switching the text to a 24 bit color
+struct Foreground(Rgb888);
+
+impl Display for Foreground {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "\x1b[38;2;{};{};{}m", self.0.r(), self.0.g(), self.0.b())
+    }
+}
+
+pub(crate) fn name_of(kind: PieceKind) -> &'static str {
+    match kind {
+        PieceKind::Long => "I",
+        PieceKind::Quad => "O",
+        PieceKind::T => "T",
+        PieceKind::Z => "Z",
+        PieceKind::L => "L",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+    use crate::LINES;
+
+    // the frame without any escape sequences
+    fn plain(frame: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = frame.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.find(|c| c.is_ascii_alphabetic());
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+
+    #[test]
+    fn only_the_first_frame_clears_the_terminal() {
+        let state = GameState::new(0);
+        let mut renderer = AnsiRenderer::new(String::new());
+
+        renderer.draw_frame(&state).unwrap();
+        let first = renderer.writer().len();
+        assert!(renderer.writer().starts_with("\x1b[2J"));
+        renderer.draw_frame(&state).unwrap();
+        assert_eq!(renderer.writer().matches("\x1b[2J").count(), 1);
+        assert!(renderer.writer()[first..].starts_with("\x1b[H"));
+
+        renderer.finish().unwrap();
+        assert!(renderer.writer().ends_with("\x1b[?25h\r\n"));
+    }
+
+    #[test]
+    fn menus_are_only_written_when_they_change() {
+        let state = GameState::new(0);
+        let mut renderer = AnsiRenderer::new(String::new());
+        renderer.draw_frame(&state).unwrap();
+        let lines = ["paused".into(), "p: resume".into()];
+        renderer.draw_menu(&lines).unwrap();
+        let written = renderer.writer().len();
+        assert!(plain(renderer.writer()).ends_with("paused\r\np: resume\r\n"));
+        renderer.draw_menu(&lines).unwrap();
+        assert_eq!(renderer.writer().len(), written);
+
+        // the game clears the menu away
+        renderer.draw_frame(&state).unwrap();
+        assert!(renderer.writer()[written..].starts_with("\x1b[2J"));
+    }
+
+    #[test]
+    fn every_line_of_the_board_is_drawn() {
+        let state = GameState::new(0);
+        let mut frame = String::new();
+        draw(
+            &state,
+            &damage::cells(&state),
+            &Appearance::default(),
+            &mut frame,
+        )
+        .unwrap();
+        // the board plus a border line above and below and the HUD line
+        assert_eq!(frame.matches("\r\n").count(), LINES + 3);
+        assert!(
+            plain(&frame)
+                .contains("score: 0  level: 0  lines: 0  time: 00:00  mode: uniform  hold: -")
+        );
+    }
+
+    #[test]
+    fn colors_and_patterns_come_from_the_appearance() {
+        let state = GameState::new(0);
+        let mut renderer = AnsiRenderer::new(String::new());
+        let theme = Theme::HIGH_CONTRAST;
+        renderer.set_appearance(Appearance {
+            theme,
+            patterns: true,
+            ..Appearance::default()
+        });
+        renderer.draw_frame(&state).unwrap();
+
+        let kind = state.falling_piece().kind();
+        let text = Foreground(theme.background);
+        let falling = format!(
+            "{}{text}{}",
+            Background(theme.piece(kind)),
+            Pattern::of(kind).glyphs()
+        );
+        let ghost = format!("{}{text}  ", Background(theme.ghost));
+        let frame = renderer.writer();
+        assert_eq!(frame.matches(&falling).count(), 4);
+        assert_eq!(frame.matches(&ghost).count(), 4);
+    }
+
+    #[test]
+    fn the_grid_marks_empty_cells() {
+        let appearance = Appearance {
+            grid: true,
+            ..Appearance::default()
+        };
+        let state = GameState::new(0);
+        let mut frame = String::new();
+        draw(&state, &damage::cells(&state), &appearance, &mut frame).unwrap();
+        // every cell but the four of the falling piece and the four of its ghost
+        assert_eq!(frame.matches(" .").count(), COLS * LINES - 8);
+    }
+}
@@ -0,0 +1,153 @@
+//! Composing frames off-screen and showing them at once.
+
+use embedded_graphics::{
+    Pixel,
+    pixelcolor::Rgb888,
+    prelude::{Dimensions, DrawTarget, OriginDimensions, Point, Size},
+    primitives::Rectangle,
+};
+
+use super::{RenderError, memory::MemoryBuffer};
+
+/// A draw target that shows what was drawn into it only once the frame is complete.
+pub trait Present {
+    /// Shows everything drawn since the last call.
+    fn present(&mut self) -> Result<(), RenderError>;
+
+    /// The frame composed last, if it is kept in memory.
+    fn frame(&self) -> Option<&MemoryBuffer> {
+        None
+    }
+}
+
+impl Present for MemoryBuffer {
+    // the buffer itself is the frame, there is nothing to show
+    fn present(&mut self) -> Result<(), RenderError> {
+        Ok(())
+    }
+
+    fn frame(&self) -> Option<&MemoryBuffer> {
+        Some(self)
+    }
+}
+
+/// A back buffer in memory in front of another draw target, the front.
+///
+/// Frames are drawn into the back buffer and copied to the front in one go on
+/// [`present`](Present::present), such that the player never sees half drawn frames. Only the
+/// region drawn to since the last present is copied.
+pub struct DoubleBuffer<D> {
+    front: D,
+    back: MemoryBuffer,
+    // corners of the region drawn to since the last present
+    dirty: Option<(Point, Point)>,
+}
+
+impl<D> DoubleBuffer<D>
+where
+    D: DrawTarget<Color = Rgb888>,
+{
+    /// Creates a back buffer as large as `front`.
+    pub fn new(front: D) -> Self {
+        let size = front.bounding_box().size;
+        Self {
+            front,
+            back: MemoryBuffer::new(size),
+            dirty: None,
+        }
+    }
+
+    /// The target frames are presented to.
+    pub fn front(&self) -> &D {
+        &self.front
+    }
+
+    /// The frame being composed.
+    pub fn back(&self) -> &MemoryBuffer {
+        &self.back
+    }
+
+    fn mark(&mut self, area: &Rectangle) {
+        let area = area.intersection(&self.back.bounding_box());
+        let Some(bottom_right) = area.bottom_right() else {
+            return;
+        };
+        let top_left = area.top_left;
+        self.dirty = Some(match self.dirty {
+            None => (top_left, bottom_right),
+            Some((min, max)) => (min.component_min(top_left), max.component_max(bottom_right)),
+        });
+    }
+}
+
+impl<D> Present for DoubleBuffer<D>
+where
+    D: DrawTarget<Color = Rgb888> + Present,
+{
+    fn present(&mut self) -> Result<(), RenderError> {
+        if let Some((min, max)) = self.dirty.take() {
+            let area = Rectangle::with_corners(min, max);
+            let frame = self.back.crop(&area);
+            self.front
+                .fill_contiguous(&area, frame.pixels().iter().copied())
+                .map_err(|_| RenderError::Display)?;
+        }
+        self.front.present()
+    }
+
+    fn frame(&self) -> Option<&MemoryBuffer> {
+        Some(&self.back)
+    }
+}
+
+impl<D> OriginDimensions for DoubleBuffer<D> {
+    fn size(&self) -> Size {
+        self.back.size()
+    }
+}
+
+impl<D> DrawTarget for DoubleBuffer<D>
+where
+    D: DrawTarget<Color = Rgb888>,
+{
+    type Color = Rgb888;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for pixel in pixels {
+            self.mark(&Rectangle::new(pixel.0, Size::new(1, 1)));
+            self.back.draw_iter([pixel])?;
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.mark(area);
+        self.back.fill_solid(area, color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::prelude::RgbColor;
+
+    use super::*;
+
+    #[test]
+    fn nothing_is_shown_before_presenting() {
+        let mut buffer = DoubleBuffer::new(MemoryBuffer::new(Size::new(8, 8)));
+        let area = Rectangle::new(Point::new(2, 3), Size::new(3, 2));
+        buffer.fill_solid(&area, Rgb888::RED).unwrap();
+        buffer
+            .draw_iter([Pixel(Point::new(7, 7), Rgb888::GREEN)])
+            .unwrap();
+        assert!(buffer.front().pixels().iter().all(|p| *p == Rgb888::BLACK));
+
+        buffer.present().unwrap();
+        assert_eq!(buffer.front(), buffer.back());
+        assert_eq!(buffer.dirty, None);
+    }
+}
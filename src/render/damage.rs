@@ -0,0 +1,141 @@
+//! Tracking which cells changed since the last frame, such that renderers only repaint those.
+
+use alloc::vec::Vec;
+
+use crate::{
+    COLS, GameState, LINES,
+    game::{
+        board::Board,
+        piece::{Cell, PieceKind},
+    },
+};
+
+/// What the player sees in a cell.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Content {
+    /// Nothing.
+    #[default]
+    Empty,
+    /// A settled block or part of the falling piece.
+    Block(PieceKind),
+    /// Part of the ghost of the falling piece.
+    Ghost(PieceKind),
+    /// Lit up by an animation.
+    Flash,
+    /// Covered by the fill of the game over animation.
+    Filled,
+}
+
+/// The content of every cell as seen by the player, including the falling piece and its ghost.
+pub type Cells = [[Content; COLS]; LINES];
+
+/// What has to be drawn for the next frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Repaint {
+    /// Nothing is known about the screen, draw everything.
+    Full,
+    /// Only these cells changed, with their new content.
+    Cells(Vec<(Cell, Content)>),
+}
+
+/// Remembers the cells shown by the last frame.
+#[derive(Debug, Clone)]
+pub struct Damage {
+    shown: Cells,
+    valid: bool,
+}
+
+impl Default for Damage {
+    fn default() -> Self {
+        Self {
+            shown: [[Content::Empty; COLS]; LINES],
+            valid: false,
+        }
+    }
+}
+
+impl Damage {
+    /// Forgets the shown cells, such that the next frame is a [`Repaint::Full`], e.g. after the
+    /// layout changed or something else drew over the frame.
+    pub fn invalidate(&mut self) {
+        self.valid = false;
+    }
+
+    /// Remembers `cells` as shown and returns what changed since the last call.
+    pub fn update(&mut self, cells: Cells) -> Repaint {
+        let repaint = if self.valid {
+            let mut changed = Vec::new();
+            for (row, (old, new)) in self.shown.iter().zip(cells.iter()).enumerate() {
+                for (col, (old, new)) in old.iter().zip(new.iter()).enumerate() {
+                    if old != new {
+                        changed.push((Cell::new(col as i32, row as i32), *new));
+                    }
+                }
+            }
+            Repaint::Cells(changed)
+        } else {
+            Repaint::Full
+        };
+        self.shown = cells;
+        self.valid = true;
+        repaint
+    }
+
+    /// The cells shown after the last [`update`](Self::update).
+    pub fn cells(&self) -> &Cells {
+        &self.shown
+    }
+}
+
+/// What the player sees of `state`, without any animations.
+pub fn cells(state: &GameState) -> Cells {
+    let mut cells = state
+        .board()
+        .rows()
+        .map(|row| row.map(|kind| kind.map_or(Content::Empty, Content::Block)));
+    if state.clearing().is_some() {
+        return cells;
+    }
+    let falling = state.falling_piece();
+    // the falling piece covers its ghost where they overlap
+    for (piece, content) in [
+        (state.ghost_piece(), Content::Ghost(falling.kind())),
+        (falling.clone(), Content::Block(falling.kind())),
+    ] {
+        for cell in piece.cells() {
+            if Board::in_bounds(*cell) {
+                cells[cell.row as usize][cell.col as usize] = content;
+            }
+        }
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Action;
+
+    #[test]
+    fn only_moved_cells_are_repainted() {
+        let mut state = GameState::new(0);
+        let mut damage = Damage::default();
+        assert_eq!(damage.update(cells(&state)), Repaint::Full);
+        assert_eq!(damage.update(cells(&state)), Repaint::Cells(Vec::new()));
+
+        let before = *state.falling_piece().cells();
+        state.step(&[Action::SoftDrop], 0);
+        let after = *state.falling_piece().cells();
+        let Repaint::Cells(changed) = damage.update(cells(&state)) else {
+            panic!("expected a partial repaint");
+        };
+        assert!(!changed.is_empty());
+        for (cell, content) in changed {
+            assert!(before.contains(&cell) != after.contains(&cell));
+            assert_eq!(content != Content::Empty, after.contains(&cell));
+        }
+
+        damage.invalidate();
+        assert_eq!(damage.update(cells(&state)), Repaint::Full);
+    }
+}
@@ -0,0 +1,239 @@
+//! A built-in 5x7 bitmap font, drawn with filled rectangles so it works on any draw target.
+
+use embedded_graphics::{
+    prelude::{DrawTarget, Point, Size},
+    primitives::Rectangle,
+};
+
+/// The size of a glyph at scale 1.
+pub const GLYPH_SIZE: Size = Size::new(5, 7);
+
+// glyph width plus one column of spacing
+const ADVANCE: u32 = GLYPH_SIZE.width + 1;
+
+/// The size of `text` drawn at `scale`.
+pub fn text_size(text: &str, scale: u32) -> Size {
+    let chars = text.chars().count() as u32;
+    Size::new(
+        (chars * ADVANCE).saturating_sub(1) * scale,
+        GLYPH_SIZE.height * scale,
+    )
+}
+
+/// Draws `text` with its top left corner at `top_left`, every font pixel `scale` pixels wide.
+/// Lowercase letters are drawn as uppercase, characters without a glyph as `?`.
+pub fn draw_text<D: DrawTarget>(
+    target: &mut D,
+    text: &str,
+    top_left: Point,
+    scale: u32,
+    color: D::Color,
+) -> Result<(), D::Error> {
+    let step = scale as i32;
+    for (idx, c) in text.chars().enumerate() {
+        let origin = top_left + Point::new(idx as i32 * ADVANCE as i32 * step, 0);
+        for (row, bits) in glyph(c).iter().enumerate() {
+            // one rectangle per run of set pixels
+            let mut col = 0;
+            while col < GLYPH_SIZE.width {
+                if bits & (1 << (GLYPH_SIZE.width - 1 - col)) == 0 {
+                    col += 1;
+                    continue;
+                }
+                let start = col;
+                while col < GLYPH_SIZE.width && bits & (1 << (GLYPH_SIZE.width - 1 - col)) != 0 {
+                    col += 1;
+                }
+                let run = Rectangle::new(
+                    origin + Point::new(start as i32 * step, row as i32 * step),
+                    Size::new((col - start) * scale, scale),
+                );
+                target.fill_solid(&run, color)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// rows from top to bottom, the leftmost pixel in the highest of the five bits
+fn glyph(c: char) -> [u8; 7] {
+    match c.to_ascii_uppercase() {
+        ' ' => [0; 7],
+        '0' => [
+            0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110,
+        ],
+        '1' => [
+            0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110,
+        ],
+        '2' => [
+            0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111,
+        ],
+        '3' => [
+            0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110,
+        ],
+        '4' => [
+            0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010,
+        ],
+        '5' => [
+            0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110,
+        ],
+        '6' => [
+            0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110,
+        ],
+        '7' => [
+            0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000,
+        ],
+        '8' => [
+            0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110,
+        ],
+        '9' => [
+            0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100,
+        ],
+        'A' => [
+            0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001,
+        ],
+        'B' => [
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110,
+        ],
+        'C' => [
+            0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110,
+        ],
+        'D' => [
+            0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100,
+        ],
+        'E' => [
+            0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111,
+        ],
+        'F' => [
+            0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000,
+        ],
+        'G' => [
+            0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111,
+        ],
+        'H' => [
+            0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001,
+        ],
+        'I' => [
+            0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110,
+        ],
+        'J' => [
+            0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100,
+        ],
+        'K' => [
+            0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001,
+        ],
+        'L' => [
+            0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111,
+        ],
+        'M' => [
+            0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001,
+        ],
+        'N' => [
+            0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001,
+        ],
+        'O' => [
+            0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110,
+        ],
+        'P' => [
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000,
+        ],
+        'Q' => [
+            0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101,
+        ],
+        'R' => [
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001,
+        ],
+        'S' => [
+            0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110,
+        ],
+        'T' => [
+            0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100,
+        ],
+        'U' => [
+            0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110,
+        ],
+        'V' => [
+            0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100,
+        ],
+        'W' => [
+            0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010,
+        ],
+        'X' => [
+            0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001,
+        ],
+        'Y' => [
+            0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100,
+        ],
+        'Z' => [
+            0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111,
+        ],
+        ':' => [
+            0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000,
+        ],
+        '-' => [
+            0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000,
+        ],
+        '.' => [
+            0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100,
+        ],
+        '/' => [
+            0b00001, 0b00010, 0b00010, 0b00100, 0b01000, 0b01000, 0b10000,
+        ],
+        _ => [
+            0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100,
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::{
+        pixelcolor::Rgb888,
+        prelude::{OriginDimensions, RgbColor},
+    };
+
+    use super::*;
+    use crate::render::memory::MemoryBuffer;
+
+    // the drawn pixels of `text` as rows of '#' and '.'
+    fn rendered(text: &str, scale: u32) -> alloc::vec::Vec<alloc::string::String> {
+        let mut buffer = MemoryBuffer::new(text_size(text, scale));
+        draw_text(&mut buffer, text, Point::zero(), scale, Rgb888::WHITE).unwrap();
+        let width = buffer.size().width as usize;
+        buffer
+            .pixels()
+            .chunks(width)
+            .map(|row| {
+                row.iter()
+                    .map(|p| if *p == Rgb888::WHITE { '#' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn glyphs_are_drawn_side_by_side() {
+        assert_eq!(
+            rendered("1l", 1),
+            [
+                "..#...#....",
+                ".##...#....",
+                "..#...#....",
+                "..#...#....",
+                "..#...#....",
+                "..#...#....",
+                ".###..#####",
+            ]
+        );
+    }
+
+    #[test]
+    fn scaling_enlarges_every_pixel() {
+        assert_eq!(text_size("ab", 3), Size::new(33, 21));
+        let small = rendered("-", 1);
+        let large = rendered("-", 2);
+        assert_eq!(large[6], "#".repeat(10));
+        assert_eq!(large[6], large[7]);
+        assert_eq!(small[3], "#####");
+    }
+}
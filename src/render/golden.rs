@@ -0,0 +1,132 @@
+//! Comparing rendered frames against stored reference images, to catch visual regressions
+//! without tinyOS or a display.
+//!
+//! References are binary PPM images in `tests/golden/`. After an intended visual change, run the
+//! tests with `TINYTETRIS_BLESS=1` to store the current frames as the new references.
+
+use std::{env, fs, path::PathBuf};
+
+use embedded_graphics::prelude::{Dimensions, OriginDimensions, Point, PointsIter, Size};
+
+use super::memory::MemoryBuffer;
+
+const BLESS_VAR: &str = "TINYTETRIS_BLESS";
+
+/// How a frame differs from its reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The frame and the reference have different sizes.
+    Size {
+        /// The size of the reference.
+        expected: Size,
+        /// The size of the frame.
+        actual: Size,
+    },
+    /// Some pixels have different colors.
+    Pixels {
+        /// How many pixels differ.
+        count: usize,
+        /// The first differing pixel, row by row.
+        first: Point,
+    },
+}
+
+/// Compares two frames pixel by pixel.
+pub fn compare(expected: &MemoryBuffer, actual: &MemoryBuffer) -> Result<(), Mismatch> {
+    if expected.size() != actual.size() {
+        return Err(Mismatch::Size {
+            expected: expected.size(),
+            actual: actual.size(),
+        });
+    }
+    let mut differing = actual
+        .bounding_box()
+        .points()
+        .filter(|point| expected.pixel(*point) != actual.pixel(*point));
+    match differing.next() {
+        None => Ok(()),
+        Some(first) => Err(Mismatch::Pixels {
+            count: differing.count() + 1,
+            first,
+        }),
+    }
+}
+
+/// The path of the reference image `name`.
+pub fn reference_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(name)
+        .with_extension("ppm")
+}
+
+/// Panics if `frame` differs from the reference image `name`. The frame is stored next to the
+/// reference as `<name>.actual.ppm` for inspection.
+pub fn assert_golden(name: &str, frame: &MemoryBuffer) {
+    let path = reference_path(name);
+    if env::var_os(BLESS_VAR).is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, frame.to_ppm()).unwrap();
+        return;
+    }
+
+    let reference = fs::read(&path)
+        .ok()
+        .and_then(|ppm| MemoryBuffer::from_ppm(&ppm))
+        .unwrap_or_else(|| {
+            panic!(
+                "missing or invalid reference {}, run with {BLESS_VAR}=1 to create it",
+                path.display()
+            )
+        });
+    if let Err(mismatch) = compare(&reference, frame) {
+        let actual = path.with_extension("actual.ppm");
+        fs::write(&actual, frame.to_ppm()).unwrap();
+        panic!(
+            "frame differs from {}: {mismatch:?}, see {}",
+            path.display(),
+            actual.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::{
+        Pixel,
+        pixelcolor::Rgb888,
+        prelude::{DrawTarget, RgbColor},
+    };
+
+    use super::*;
+
+    #[test]
+    fn comparison_reports_the_first_difference() {
+        let expected = MemoryBuffer::new(Size::new(3, 3));
+        let mut actual = expected.clone();
+        assert_eq!(compare(&expected, &actual), Ok(()));
+
+        actual
+            .draw_iter([
+                Pixel(Point::new(2, 2), Rgb888::RED),
+                Pixel(Point::new(1, 1), Rgb888::RED),
+            ])
+            .unwrap();
+        assert_eq!(
+            compare(&expected, &actual),
+            Err(Mismatch::Pixels {
+                count: 2,
+                first: Point::new(1, 1)
+            })
+        );
+
+        assert_eq!(
+            compare(&expected, &MemoryBuffer::new(Size::new(3, 2))),
+            Err(Mismatch::Size {
+                expected: Size::new(3, 3),
+                actual: Size::new(3, 2)
+            })
+        );
+    }
+}
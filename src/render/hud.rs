@@ -0,0 +1,55 @@
+//! The values shown next to the board while playing.
+
+use alloc::{format, string::String};
+
+use crate::{GameState, game::randomizer::RandomizerKind};
+
+/// A labeled value of the HUD.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// What the value means, in lowercase.
+    pub label: &'static str,
+    /// The value, formatted for display.
+    pub value: String,
+}
+
+/// Score, level, lines, elapsed time and mode of `state`, in display order.
+pub fn entries(state: &GameState) -> [Entry; 5] {
+    let score = state.score();
+    [
+        entry("score", format!("{}", score.points())),
+        entry("level", format!("{}", score.level())),
+        entry("lines", format!("{}", score.lines())),
+        entry("time", format_time(state.elapsed())),
+        entry("mode", String::from(mode_name(state.randomizer().kind()))),
+    ]
+}
+
+fn entry(label: &'static str, value: String) -> Entry {
+    Entry { label, value }
+}
+
+// minutes and seconds, minutes keep growing past an hour
+fn format_time(ms: u32) -> String {
+    let seconds = ms / 1000;
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn mode_name(kind: RandomizerKind) -> &'static str {
+    match kind {
+        RandomizerKind::Uniform => "uniform",
+        RandomizerKind::Bag => "bag",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_is_shown_in_minutes_and_seconds() {
+        assert_eq!(format_time(0), "00:00");
+        assert_eq!(format_time(61_999), "01:01");
+        assert_eq!(format_time(3_600_000), "60:00");
+    }
+}
@@ -0,0 +1,142 @@
+//! Placing the board and the panels around it on screens of any size.
+
+use embedded_graphics::{
+    prelude::{Point, Size},
+    primitives::Rectangle,
+};
+
+use crate::{COLS, LINES, game::piece::Cell};
+
+// widths in blocks, from left to right: margin, hold panel, gap, board, gap, side column, margin
+const HOLD_COLS: u32 = 6;
+const SIDE_COLS: u32 = 7;
+const GAP: u32 = 1;
+const WIDTH: u32 = GAP + HOLD_COLS + GAP + COLS as u32 + GAP + SIDE_COLS + GAP;
+const HEIGHT: u32 = GAP + LINES as u32 + GAP;
+// the hold and preview panels are square
+const PANEL_ROWS: u32 = HOLD_COLS;
+
+/// Where everything is drawn, in pixels.
+///
+/// The game is scaled by an integer block size to the largest size fitting the screen and
+/// centered on it. Text is scaled along with the blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    /// The edge length of a block.
+    pub block: u32,
+    /// The width of the border around the board.
+    pub border: u32,
+    /// The board without its border.
+    pub board: Rectangle,
+    /// The panel showing the held piece, left of the board.
+    pub hold: Rectangle,
+    /// The panel showing the next piece, right of the board.
+    pub preview: Rectangle,
+    /// The score and friends, below the preview.
+    pub hud: Rectangle,
+    /// The scale of the [`font`](super::font) for all text.
+    pub text_scale: u32,
+}
+
+impl Layout {
+    /// The layout for a screen of `size`. Screens too small for even one pixel per block get a
+    /// layout sticking out of the screen.
+    pub fn fit(size: Size) -> Self {
+        let block = (size.width / WIDTH).min(size.height / HEIGHT).max(1);
+        let origin = Point::new(
+            (size.width.saturating_sub(WIDTH * block) / 2) as i32,
+            (size.height.saturating_sub(HEIGHT * block) / 2) as i32,
+        );
+        // area of `cols` x `rows` blocks, `col` and `row` blocks from the origin
+        let area = |col: u32, row: u32, cols: u32, rows: u32| {
+            Rectangle::new(
+                origin + Point::new((col * block) as i32, (row * block) as i32),
+                Size::new(cols * block, rows * block),
+            )
+        };
+
+        let board_col = GAP + HOLD_COLS + GAP;
+        let side_col = board_col + COLS as u32 + GAP;
+        Self {
+            block,
+            border: block.div_ceil(3),
+            board: area(board_col, GAP, COLS as u32, LINES as u32),
+            hold: area(GAP, GAP, HOLD_COLS, PANEL_ROWS),
+            preview: area(side_col, GAP, SIDE_COLS, PANEL_ROWS),
+            hud: area(
+                side_col,
+                GAP + PANEL_ROWS + GAP,
+                SIDE_COLS,
+                LINES as u32 - PANEL_ROWS - GAP,
+            ),
+            text_scale: (block / 8).max(1),
+        }
+    }
+
+    /// The area covered by `cell` of the board.
+    pub fn cell(&self, cell: Cell) -> Rectangle {
+        let block = self.block as i32;
+        Rectangle::new(
+            self.board.top_left + Point::new(cell.col * block, cell.row * block),
+            Size::new(self.block, self.block),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::prelude::Dimensions;
+
+    use super::*;
+
+    #[test]
+    fn blocks_scale_with_the_screen() {
+        assert_eq!(Layout::fit(Size::new(320, 240)).block, 8);
+        assert_eq!(Layout::fit(Size::new(640, 480)).block, 17);
+        assert_eq!(Layout::fit(Size::new(1920, 1080)).block, 49);
+        assert_eq!(Layout::fit(Size::new(10, 10)).block, 1);
+    }
+
+    #[test]
+    fn everything_fits_centered_on_the_screen() {
+        for size in [
+            Size::new(320, 240),
+            Size::new(640, 480),
+            Size::new(800, 600),
+            Size::new(1280, 800),
+            Size::new(1920, 1080),
+        ] {
+            let screen = Rectangle::new(Point::zero(), size);
+            let layout = Layout::fit(size);
+            let border = layout.border as i32;
+            for area in [
+                layout.board.offset(border),
+                layout.hold,
+                layout.preview,
+                layout.hud,
+            ] {
+                assert_eq!(screen.intersection(&area), area, "{size:?}");
+            }
+            assert_eq!(
+                layout.board.intersection(&layout.hold).size,
+                Size::zero(),
+                "{size:?}"
+            );
+
+            // the margins left and right of the game differ by at most one pixel
+            let left = layout.hold.top_left.x - layout.block as i32;
+            let right = size.width as i32
+                - layout.preview.bottom_right().unwrap().x
+                - 1
+                - layout.block as i32;
+            assert!((left - right).abs() <= 1, "{size:?}");
+            assert_eq!(layout.cell(Cell::new(0, 0)).top_left, layout.board.top_left);
+            assert_eq!(
+                layout
+                    .cell(Cell::new(COLS as i32 - 1, LINES as i32 - 1))
+                    .bottom_right(),
+                layout.board.bounding_box().bottom_right()
+            );
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! A framebuffer in plain memory, for rendering without any display.
+
+use alloc::{format, vec, vec::Vec};
+use core::convert::Infallible;
+
+use embedded_graphics::{
+    Pixel,
+    pixelcolor::Rgb888,
+    prelude::{DrawTarget, OriginDimensions, Point, PointsIter, RgbColor, Size},
+    primitives::Rectangle,
+};
+
+/// `width * height` pixels stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryBuffer {
+    size: Size,
+    pixels: Vec<Rgb888>,
+}
+
+impl MemoryBuffer {
+    /// Creates a black buffer of `size`.
+    pub fn new(size: Size) -> Self {
+        Self {
+            size,
+            pixels: vec![Rgb888::BLACK; (size.width * size.height) as usize],
+        }
+    }
+
+    /// The color at `point`, `None` if it lies outside of the buffer.
+    pub fn pixel(&self, point: Point) -> Option<Rgb888> {
+        self.index(point).map(|idx| self.pixels[idx])
+    }
+
+    /// All pixels, row by row.
+    pub fn pixels(&self) -> &[Rgb888] {
+        &self.pixels
+    }
+
+    /// A copy of the part of this buffer covered by `area`, parts outside of the buffer are black.
+    pub fn crop(&self, area: &Rectangle) -> Self {
+        let mut cropped = Self::new(area.size);
+        for (idx, point) in area.points().enumerate() {
+            if let Some(color) = self.pixel(point) {
+                cropped.pixels[idx] = color;
+            }
+        }
+        cropped
+    }
+
+    /// Encodes this buffer as a binary PPM image.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.size.width, self.size.height).into_bytes();
+        ppm.reserve(self.pixels.len() * 3);
+        for pixel in self.pixels.iter() {
+            ppm.extend_from_slice(&[pixel.r(), pixel.g(), pixel.b()]);
+        }
+        ppm
+    }
+
+    /// Encodes this buffer as an uncompressed 24 bit BMP image.
+    pub fn to_bmp(&self) -> Vec<u8> {
+        const HEADERS: u32 = 14 + 40;
+        // every row is padded to a multiple of four bytes
+        let row = (self.size.width * 3).next_multiple_of(4);
+        let data = row * self.size.height;
+
+        let mut bmp = Vec::with_capacity((HEADERS + data) as usize);
+        // file header
+        bmp.extend_from_slice(b"BM");
+        bmp.extend_from_slice(&(HEADERS + data).to_le_bytes());
+        bmp.extend_from_slice(&0_u32.to_le_bytes());
+        bmp.extend_from_slice(&HEADERS.to_le_bytes());
+        // info header, a negative height stores the rows from top to bottom
+        bmp.extend_from_slice(&40_u32.to_le_bytes());
+        bmp.extend_from_slice(&(self.size.width as i32).to_le_bytes());
+        bmp.extend_from_slice(&(-(self.size.height as i32)).to_le_bytes());
+        bmp.extend_from_slice(&1_u16.to_le_bytes());
+        bmp.extend_from_slice(&24_u16.to_le_bytes());
+        for field in [0, data, 2835, 2835, 0, 0] {
+            bmp.extend_from_slice(&field.to_le_bytes());
+        }
+
+        for line in self.pixels.chunks(self.size.width.max(1) as usize) {
+            let start = bmp.len();
+            for pixel in line {
+                bmp.extend_from_slice(&[pixel.b(), pixel.g(), pixel.r()]);
+            }
+            bmp.resize(start + row as usize, 0);
+        }
+        bmp
+    }
+
+    /// Decodes a binary PPM image with 8 bit channels, as written by [`to_ppm`](Self::to_ppm).
+    pub fn from_ppm(ppm: &[u8]) -> Option<Self> {
+        let mut rest = ppm;
+        let mut header = [0_u32; 3];
+        if next_token(&mut rest)? != b"P6" {
+            return None;
+        }
+        for field in header.iter_mut() {
+            *field = str::from_utf8(next_token(&mut rest)?).ok()?.parse().ok()?;
+        }
+        let [width, height, max] = header;
+        // exactly one whitespace byte separates the header from the pixels
+        let data = rest.get(1..)?;
+        let len = width.checked_mul(height)?.checked_mul(3)?;
+        if max != 255 || data.len() != usize::try_from(len).ok()? {
+            return None;
+        }
+
+        Some(Self {
+            size: Size::new(width, height),
+            pixels: data
+                .as_chunks::<3>()
+                .0
+                .iter()
+                .map(|&[r, g, b]| Rgb888::new(r, g, b))
+                .collect(),
+        })
+    }
+
+    fn index(&self, point: Point) -> Option<usize> {
+        let (x, y) = (u32::try_from(point.x).ok()?, u32::try_from(point.y).ok()?);
+        (x < self.size.width && y < self.size.height).then_some((y * self.size.width + x) as usize)
+    }
+}
+
+// splits off the next whitespace separated token of a PPM header, skipping comments
+fn next_token<'a>(rest: &mut &'a [u8]) -> Option<&'a [u8]> {
+    loop {
+        let start = rest.iter().position(|byte| !byte.is_ascii_whitespace())?;
+        *rest = &rest[start..];
+        if rest[0] != b'#' {
+            break;
+        }
+        let end = rest.iter().position(|byte| *byte == b'\n')?;
+        *rest = &rest[end..];
+    }
+    let end = rest
+        .iter()
+        .position(|byte| byte.is_ascii_whitespace())
+        .unwrap_or(rest.len());
+    let (token, tail) = rest.split_at(end);
+    *rest = tail;
+    Some(token)
+}
+
+impl OriginDimensions for MemoryBuffer {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl DrawTarget for MemoryBuffer {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let Some(idx) = self.index(point) {
+                self.pixels[idx] = color;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixels_outside_of_the_buffer_are_ignored() {
+        let mut buffer = MemoryBuffer::new(Size::new(4, 3));
+        buffer
+            .draw_iter([
+                Pixel(Point::new(1, 2), Rgb888::RED),
+                Pixel(Point::new(4, 0), Rgb888::GREEN),
+                Pixel(Point::new(-1, 0), Rgb888::GREEN),
+            ])
+            .unwrap();
+
+        assert_eq!(buffer.pixel(Point::new(1, 2)), Some(Rgb888::RED));
+        assert_eq!(buffer.pixel(Point::new(4, 0)), None);
+        assert_eq!(
+            buffer
+                .pixels()
+                .iter()
+                .filter(|p| **p != Rgb888::BLACK)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn ppm_round_trips() {
+        let mut buffer = MemoryBuffer::new(Size::new(3, 2));
+        buffer
+            .draw_iter([
+                Pixel(Point::new(0, 0), Rgb888::new(1, 2, 3)),
+                Pixel(Point::new(2, 1), Rgb888::WHITE),
+            ])
+            .unwrap();
+
+        let ppm = buffer.to_ppm();
+        assert!(ppm.starts_with(b"P6\n3 2\n255\n"));
+        assert_eq!(MemoryBuffer::from_ppm(&ppm), Some(buffer));
+    }
+
+    #[test]
+    fn bmp_rows_are_padded() {
+        let mut buffer = MemoryBuffer::new(Size::new(3, 2));
+        buffer
+            .draw_iter([Pixel(Point::new(0, 1), Rgb888::new(1, 2, 3))])
+            .unwrap();
+
+        let bmp = buffer.to_bmp();
+        // two rows of nine bytes, each padded to twelve
+        assert_eq!(bmp.len(), 54 + 2 * 12);
+        assert!(bmp.starts_with(b"BM"));
+        assert_eq!(bmp[2..6], (bmp.len() as u32).to_le_bytes());
+        assert_eq!(bmp[22..26], (-2_i32).to_le_bytes());
+        assert_eq!(bmp[54 + 12..54 + 15], [3, 2, 1]);
+    }
+
+    #[test]
+    fn ppm_headers_may_contain_comments() {
+        let ppm = b"P6 # made by hand\n1 1\n255\n\x01\x02\x03";
+        let buffer = MemoryBuffer::from_ppm(ppm).unwrap();
+        assert_eq!(buffer.pixel(Point::zero()), Some(Rgb888::new(1, 2, 3)));
+
+        assert_eq!(MemoryBuffer::from_ppm(b"P3\n1 1\n255\n1 2 3"), None);
+        assert_eq!(MemoryBuffer::from_ppm(b"P6\n2 1\n255\n\x01\x02\x03"), None);
+        // sizes whose pixels overflow are no valid images
+        assert_eq!(MemoryBuffer::from_ppm(b"P6\n65536 65536\n255\n"), None);
+    }
+
+    #[test]
+    fn cropping_keeps_the_covered_pixels() {
+        let mut buffer = MemoryBuffer::new(Size::new(4, 4));
+        buffer
+            .draw_iter([Pixel(Point::new(2, 3), Rgb888::RED)])
+            .unwrap();
+
+        let cropped = buffer.crop(&Rectangle::new(Point::new(2, 2), Size::new(3, 2)));
+        assert_eq!(cropped.size(), Size::new(3, 2));
+        assert_eq!(cropped.pixel(Point::new(0, 1)), Some(Rgb888::RED));
+        assert_eq!(cropped.pixel(Point::new(2, 1)), Some(Rgb888::BLACK));
+    }
+}
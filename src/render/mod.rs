@@ -0,0 +1,109 @@
+//! Drawing frames of a running game.
+//!
+//! A [`Renderer`] only gets a read-only view of the [`GameState`] and draws a frame from it,
+//! possibly repainting only what changed since the previous one (see [`damage`]). Frontends pick
+//! one of the backends at startup:
+//! - [`pixel::PixelRenderer`] draws into any `embedded-graphics` draw target, e.g. the tinyOS
+//!   kernel framebuffer behind a [`buffer::DoubleBuffer`] or a [`memory::MemoryBuffer`],
+//! - [`ansi::AnsiRenderer`] writes text with ANSI escape sequences to a terminal.
+
+use alloc::string::String;
+
+use crate::{GameState, Subscriber};
+use screenshot::Screenshot;
+use style::BlockStyle;
+use theme::Theme;
+
+pub mod animation;
+pub mod ansi;
+pub mod buffer;
+pub mod damage;
+pub mod font;
+#[cfg(test)]
+mod golden;
+pub mod hud;
+pub mod layout;
+pub mod memory;
+pub mod pattern;
+pub mod pixel;
+pub mod screenshot;
+pub mod settings;
+pub mod style;
+pub mod theme;
+
+/// The ways drawing a frame can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum RenderError {
+    /// The draw target rejected a primitive.
+    #[error("drawing to the display failed")]
+    Display,
+    /// The text output could not be written.
+    #[error("writing the frame to the terminal failed")]
+    Terminal,
+    /// The display was not set up before drawing.
+    #[error("the display is not initialized")]
+    Uninitialized,
+}
+
+impl From<core::fmt::Error> for RenderError {
+    fn from(_: core::fmt::Error) -> Self {
+        Self::Terminal
+    }
+}
+
+/// How the game looks, shared by all renderers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Appearance {
+    /// The colors.
+    pub theme: Theme,
+    /// Whether blocks show a [`pattern::Pattern`] per piece kind, so they can be told apart
+    /// without their colors.
+    pub patterns: bool,
+    /// How blocks are drawn.
+    pub block_style: BlockStyle,
+    /// Whether empty cells show a faint grid.
+    pub grid: bool,
+    /// Whether line clears, locks and game overs are [animated](animation).
+    pub animations: bool,
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            patterns: false,
+            block_style: BlockStyle::default(),
+            grid: false,
+            animations: true,
+        }
+    }
+}
+
+/// Something that can show the game to the player.
+///
+/// Renderers are [`Subscriber`]s so they can start animations when something happens. Frontends
+/// pass them every [`Event`](crate::Event) returned by [`GameState::step`].
+pub trait Renderer: Subscriber {
+    /// Draws one frame showing `state`.
+    fn draw_frame(&mut self, state: &GameState) -> Result<(), RenderError>;
+
+    /// Draws a screen of text instead of the game, e.g. a menu. The first line is a title. Drawing
+    /// the same lines again does nothing, the next frame repaints the game.
+    fn draw_menu(&mut self, lines: &[String]) -> Result<(), RenderError>;
+
+    /// The frame drawn last as it was composed, for saving it. `None` if the renderer keeps no
+    /// frame.
+    fn screenshot(&self) -> Option<Screenshot<'_>>;
+
+    /// Draws the following frames with `appearance`.
+    fn set_appearance(&mut self, appearance: Appearance);
+
+    /// Makes the next frame repaint everything, e.g. after the layout changed or something else
+    /// drew over the previous frame.
+    fn invalidate(&mut self) {}
+
+    /// Called once when the frontend stops drawing, to restore whatever the renderer changed.
+    fn finish(&mut self) -> Result<(), RenderError> {
+        Ok(())
+    }
+}
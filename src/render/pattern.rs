@@ -0,0 +1,122 @@
+//! Patterns inside the blocks, telling the piece kinds apart without relying on colors.
+
+use alloc::vec::Vec;
+
+use embedded_graphics::{
+    prelude::{Point, Size},
+    primitives::Rectangle,
+};
+
+use crate::game::piece::PieceKind;
+
+/// A pattern drawn on top of every block of a piece kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    /// Two vertical stripes.
+    VerticalStripes,
+    /// A dot in the center.
+    Dot,
+    /// A plus sign.
+    Cross,
+    /// A diagonal from the top left to the bottom right.
+    Diagonal,
+    /// Two horizontal stripes.
+    HorizontalStripes,
+}
+
+impl Pattern {
+    /// The pattern of pieces of `kind`.
+    pub fn of(kind: PieceKind) -> Self {
+        match kind {
+            PieceKind::Long => Self::VerticalStripes,
+            PieceKind::Quad => Self::Dot,
+            PieceKind::T => Self::Cross,
+            PieceKind::Z => Self::Diagonal,
+            PieceKind::L => Self::HorizontalStripes,
+        }
+    }
+
+    /// The parts of the square `block` to fill with the pattern color.
+    pub fn areas(self, block: &Rectangle) -> Vec<Rectangle> {
+        let edge = block.size.width as i32;
+        // stroke width, growing with the block
+        let stroke = (edge / 8).max(1);
+        let at = |x: i32, y: i32, width: i32, height: i32| {
+            Rectangle::new(
+                block.top_left + Point::new(x, y),
+                Size::new(width as u32, height as u32),
+            )
+        };
+        let quarter = edge / 4;
+        // mirrors the stripe at `quarter`
+        let three_quarters = edge - quarter - stroke;
+        let (center, third) = ((edge - stroke) / 2, (edge / 3).max(1));
+        match self {
+            Self::VerticalStripes => alloc::vec![
+                at(quarter, 0, stroke, edge),
+                at(three_quarters, 0, stroke, edge),
+            ],
+            Self::HorizontalStripes => alloc::vec![
+                at(0, quarter, edge, stroke),
+                at(0, three_quarters, edge, stroke),
+            ],
+            Self::Dot => {
+                let start = (edge - third) / 2;
+                alloc::vec![at(start, start, third, third)]
+            }
+            Self::Cross => alloc::vec![
+                at(quarter, center, edge - 2 * quarter, stroke),
+                at(center, quarter, stroke, edge - 2 * quarter),
+            ],
+            Self::Diagonal => (0..edge)
+                .map(|step| at(step, step, stroke.min(edge - step), 1))
+                .collect(),
+        }
+    }
+
+    /// Two characters showing the pattern in text.
+    pub fn glyphs(self) -> &'static str {
+        match self {
+            Self::VerticalStripes => "||",
+            Self::Dot => "()",
+            Self::Cross => "++",
+            Self::Diagonal => "\\\\",
+            Self::HorizontalStripes => "==",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::prelude::PointsIter;
+
+    use super::*;
+
+    #[test]
+    fn patterns_stay_inside_their_block_and_differ() {
+        for edge in [1, 5, 8, 17, 49] {
+            let block = Rectangle::new(Point::new(3, 7), Size::new(edge, edge));
+            let mut covered = Vec::new();
+            for kind in PieceKind::ALL {
+                let pattern = Pattern::of(kind);
+                let mut points = pattern
+                    .areas(&block)
+                    .iter()
+                    .flat_map(|area| {
+                        assert_eq!(block.intersection(area), *area, "{pattern:?} {edge}");
+                        area.points()
+                    })
+                    .collect::<Vec<_>>();
+                points.sort_by_key(|point| (point.y, point.x));
+                points.dedup();
+                assert!(!points.is_empty(), "{pattern:?} {edge}");
+                covered.push(points);
+            }
+            if edge >= 8 {
+                for (idx, points) in covered.iter().enumerate() {
+                    assert!(!covered[idx + 1..].contains(points), "{edge}");
+                }
+            }
+        }
+    }
+}
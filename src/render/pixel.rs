@@ -0,0 +1,504 @@
+//! Drawing the game with pixels.
+
+use alloc::{string::String, vec, vec::Vec};
+
+use embedded_graphics::{
+    Drawable,
+    pixelcolor::Rgb888,
+    prelude::{DrawTarget, Point, Primitive, Size},
+    primitives::{PrimitiveStyleBuilder, Rectangle, StrokeAlignment},
+};
+
+use super::{
+    Appearance, RenderError, Renderer,
+    animation::Animations,
+    buffer::Present,
+    damage::{self, Content, Damage, Repaint},
+    font,
+    hud::{self, Entry},
+    layout::Layout,
+    pattern::Pattern,
+    screenshot::Screenshot,
+    style::blend,
+};
+use crate::{
+    Event, GameState, Subscriber,
+    game::piece::{Cell, Piece, PieceKind},
+};
+
+/// A [`Renderer`] drawing into any `embedded-graphics` draw target that can
+/// [`Present`] complete frames, e.g. a [`DoubleBuffer`](super::buffer::DoubleBuffer).
+///
+/// The game is laid out to fit the size of the target, see [`Layout`]. Only what changed since the
+/// last frame is repainted. The target must therefore keep its content between frames, otherwise
+/// call [`Renderer::invalidate`] before drawing.
+pub struct PixelRenderer<D> {
+    target: D,
+    layout: Layout,
+    appearance: Appearance,
+    damage: Damage,
+    animations: Animations,
+    // the panels shown by the last frame
+    panels: Option<Panels>,
+    // the lines shown by the last menu, if it is still shown
+    menu: Option<Vec<String>>,
+}
+
+// everything drawn around the board
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Panels {
+    hold: Option<PieceKind>,
+    next: PieceKind,
+    hud: [Entry; 5],
+}
+
+impl Panels {
+    fn of(state: &GameState) -> Self {
+        Self {
+            hold: state.held_piece(),
+            next: state.next_kind(),
+            hud: hud::entries(state),
+        }
+    }
+}
+
+impl<D> PixelRenderer<D>
+where
+    D: DrawTarget<Color = Rgb888>,
+{
+    /// Creates a renderer drawing into `target`, laid out for the size of the target.
+    pub fn new(target: D) -> Self {
+        let layout = Layout::fit(target.bounding_box().size);
+        Self {
+            target,
+            layout,
+            appearance: Appearance::default(),
+            damage: Damage::default(),
+            animations: Animations::default(),
+            panels: None,
+            menu: None,
+        }
+    }
+
+    /// Where everything is drawn.
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    /// Draws the following frames with `layout`, starting with a full repaint.
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.layout = layout;
+        self.damage.invalidate();
+    }
+
+    /// The target frames are drawn into.
+    pub fn target(&self) -> &D {
+        &self.target
+    }
+
+    /// Returns the target, e.g. to inspect the last frame.
+    pub fn into_target(self) -> D {
+        self.target
+    }
+
+    fn draw_board(&mut self) -> Result<(), RenderError> {
+        self.target
+            .clear(self.appearance.theme.background)
+            .map_err(|_| RenderError::Display)?;
+        self.layout
+            .board
+            .into_styled(
+                PrimitiveStyleBuilder::new()
+                    .stroke_color(self.appearance.theme.border)
+                    .stroke_alignment(StrokeAlignment::Outside)
+                    .stroke_width(self.layout.border)
+                    .build(),
+            )
+            .draw(&mut self.target)
+            .map_err(|_| RenderError::Display)
+    }
+
+    fn draw_cell(&mut self, area: Rectangle, content: Content) -> Result<(), RenderError> {
+        let appearance = self.appearance;
+        let theme = &appearance.theme;
+        let mut parts = match content {
+            Content::Empty => vec![(area, theme.background)],
+            Content::Ghost(_) => vec![(area, theme.ghost)],
+            Content::Flash => vec![(area, theme.text)],
+            Content::Filled => appearance
+                .block_style
+                .parts(&area, theme.border, theme.background),
+            Content::Block(kind) => {
+                appearance
+                    .block_style
+                    .parts(&area, theme.piece(kind), theme.background)
+            }
+        };
+        match content {
+            Content::Empty if appearance.grid => {
+                // lines along the top and left edge add up to a grid
+                let line = blend(theme.background, theme.border, 2);
+                parts.push((
+                    Rectangle::new(area.top_left, Size::new(area.size.width, 1)),
+                    line,
+                ));
+                parts.push((
+                    Rectangle::new(area.top_left, Size::new(1, area.size.height)),
+                    line,
+                ));
+            }
+            Content::Block(kind) if appearance.patterns => parts.extend(
+                Pattern::of(kind)
+                    .areas(&area)
+                    .into_iter()
+                    .map(|part| (part, theme.background)),
+            ),
+            _ => {}
+        }
+
+        for (part, color) in parts {
+            self.target
+                .fill_solid(&part, color)
+                .map_err(|_| RenderError::Display)?;
+        }
+        Ok(())
+    }
+
+    fn draw_text(&mut self, text: &str, top_left: Point, color: Rgb888) -> Result<(), RenderError> {
+        font::draw_text(
+            &mut self.target,
+            text,
+            top_left,
+            self.layout.text_scale,
+            color,
+        )
+        .map_err(|_| RenderError::Display)
+    }
+
+    fn clear(&mut self, area: Rectangle) -> Result<(), RenderError> {
+        self.target
+            .fill_solid(&area, self.appearance.theme.background)
+            .map_err(|_| RenderError::Display)
+    }
+
+    // a labeled panel with a piece in its spawn orientation centered below the label
+    fn draw_panel(
+        &mut self,
+        area: Rectangle,
+        label: &str,
+        kind: Option<PieceKind>,
+    ) -> Result<(), RenderError> {
+        self.clear(area)?;
+        self.draw_text(label, area.top_left, self.appearance.theme.label)?;
+        let Some(kind) = kind else {
+            return Ok(());
+        };
+
+        let piece = Piece::spawn(kind);
+        let cells = piece.cells();
+        let min = |f: fn(&Cell) -> i32| cells.iter().map(f).min().unwrap_or(0);
+        let max = |f: fn(&Cell) -> i32| cells.iter().map(f).max().unwrap_or(0);
+        let (left, top) = (min(|cell| cell.col), min(|cell| cell.row));
+        let block = self.layout.block as i32;
+        let size = Point::new(
+            (max(|cell| cell.col) - left + 1) * block,
+            (max(|cell| cell.row) - top + 1) * block,
+        );
+        let label_height = (font::GLYPH_SIZE.height * self.layout.text_scale) as i32;
+        let below_label = Rectangle::new(
+            area.top_left + Point::new(0, label_height),
+            area.size - Size::new(0, label_height as u32),
+        );
+        let origin = below_label.center() - size / 2;
+        for cell in cells {
+            let block_area = Rectangle::new(
+                origin + Point::new((cell.col - left) * block, (cell.row - top) * block),
+                Size::new(self.layout.block, self.layout.block),
+            );
+            self.draw_cell(block_area, Content::Block(kind))?;
+        }
+        Ok(())
+    }
+
+    fn draw_hud(&mut self, entries: &[Entry]) -> Result<(), RenderError> {
+        let area = self.layout.hud;
+        self.clear(area)?;
+        // a line of text plus two pixels of spacing, in font pixels
+        let line = ((font::GLYPH_SIZE.height + 2) * self.layout.text_scale) as i32;
+        let mut top_left = area.top_left;
+        for entry in entries {
+            self.draw_text(entry.label, top_left, self.appearance.theme.label)?;
+            top_left.y += line;
+            self.draw_text(&entry.value, top_left, self.appearance.theme.text)?;
+            top_left.y += line;
+        }
+        Ok(())
+    }
+
+    fn draw_panels(&mut self, panels: Panels) -> Result<(), RenderError> {
+        let shown = self.panels.take();
+        let shown = shown.as_ref();
+        if shown.map(|shown| shown.hold) != Some(panels.hold) {
+            self.draw_panel(self.layout.hold, "hold", panels.hold)?;
+        }
+        if shown.map(|shown| shown.next) != Some(panels.next) {
+            self.draw_panel(self.layout.preview, "next", Some(panels.next))?;
+        }
+        if shown.map(|shown| &shown.hud) != Some(&panels.hud) {
+            self.draw_hud(&panels.hud)?;
+        }
+        self.panels = Some(panels);
+        Ok(())
+    }
+}
+
+impl<D> Renderer for PixelRenderer<D>
+where
+    D: DrawTarget<Color = Rgb888> + Present,
+{
+    fn draw_frame(&mut self, state: &GameState) -> Result<(), RenderError> {
+        if self.menu.take().is_some() {
+            self.damage.invalidate();
+        }
+        let mut cells = damage::cells(state);
+        if self.appearance.animations {
+            self.animations.apply(state, &mut cells);
+        }
+        match self.damage.update(cells) {
+            Repaint::Full => {
+                self.panels = None;
+                self.draw_board()?;
+                let cells = *self.damage.cells();
+                for (row, line) in cells.iter().enumerate() {
+                    for (col, kind) in line.iter().enumerate() {
+                        if *kind != Content::Empty || self.appearance.grid {
+                            let area = self.layout.cell(Cell::new(col as i32, row as i32));
+                            self.draw_cell(area, *kind)?;
+                        }
+                    }
+                }
+            }
+            Repaint::Cells(changed) => {
+                for (cell, kind) in changed {
+                    self.draw_cell(self.layout.cell(cell), kind)?;
+                }
+            }
+        }
+        self.draw_panels(Panels::of(state))?;
+        self.target.present()
+    }
+
+    fn draw_menu(&mut self, lines: &[String]) -> Result<(), RenderError> {
+        if self.menu.as_deref() == Some(lines) {
+            return Ok(());
+        }
+        self.target
+            .clear(self.appearance.theme.background)
+            .map_err(|_| RenderError::Display)?;
+        // left aligned with the hold panel, starting at the top of the board
+        let mut top_left = Point::new(self.layout.hold.top_left.x, self.layout.board.top_left.y);
+        let line = ((font::GLYPH_SIZE.height + 2) * self.layout.text_scale) as i32;
+        for (idx, text) in lines.iter().enumerate() {
+            let color = if idx == 0 {
+                self.appearance.theme.label
+            } else {
+                self.appearance.theme.text
+            };
+            self.draw_text(text, top_left, color)?;
+            top_left.y += line;
+        }
+        self.menu = Some(lines.to_vec());
+        self.target.present()
+    }
+
+    fn screenshot(&self) -> Option<Screenshot<'_>> {
+        self.target.frame().map(Screenshot::Image)
+    }
+
+    fn set_appearance(&mut self, appearance: Appearance) {
+        self.appearance = appearance;
+        self.damage.invalidate();
+        self.menu = None;
+    }
+
+    fn invalidate(&mut self) {
+        self.damage.invalidate();
+        self.menu = None;
+    }
+}
+
+impl<D> Subscriber for PixelRenderer<D> {
+    fn notify(&mut self, event: &Event) {
+        self.animations.notify(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use embedded_graphics::prelude::RgbColor;
+
+    use super::*;
+    use crate::{
+        Action, Event,
+        render::style::BlockStyle,
+        render::{golden::assert_golden, memory::MemoryBuffer},
+    };
+
+    // small enough to keep the reference images small
+    const FRAME: Size = Size::new(320, 240);
+
+    fn render(state: &GameState) -> MemoryBuffer {
+        let mut renderer = PixelRenderer::new(MemoryBuffer::new(FRAME));
+        renderer.draw_frame(state).unwrap();
+        renderer.into_target()
+    }
+
+    #[test]
+    fn falling_piece_is_drawn_in_its_color() {
+        let state = GameState::new(0);
+        let mut renderer = PixelRenderer::new(MemoryBuffer::new(Size::new(640, 480)));
+        renderer.draw_frame(&state).unwrap();
+
+        let layout = *renderer.layout();
+        let buffer = renderer.into_target();
+        let falling = state.falling_piece();
+        for cell in falling.cells() {
+            let center = layout.cell(*cell).center();
+            assert_eq!(
+                buffer.pixel(center),
+                Some(Appearance::default().theme.piece(falling.kind()))
+            );
+        }
+        let bottom_left = layout.board.top_left + Point::new(0, layout.board.size.height as i32);
+        assert_eq!(
+            buffer.pixel(bottom_left - Point::new(-1, 1)),
+            Some(Rgb888::BLACK)
+        );
+        assert_eq!(
+            buffer.pixel(layout.board.top_left - Point::new(1, 0)),
+            Some(Rgb888::WHITE)
+        );
+    }
+
+    #[test]
+    fn patterns_are_drawn_over_blocks() {
+        let state = GameState::new(0);
+        let mut renderer = PixelRenderer::new(MemoryBuffer::new(FRAME));
+        let appearance = Appearance {
+            patterns: true,
+            ..Appearance::default()
+        };
+        renderer.set_appearance(appearance);
+        renderer.draw_frame(&state).unwrap();
+
+        let layout = *renderer.layout();
+        let buffer = renderer.into_target();
+        let falling = state.falling_piece();
+        for cell in falling.cells() {
+            let block = layout.cell(*cell);
+            for part in Pattern::of(falling.kind()).areas(&block) {
+                assert_eq!(
+                    buffer.pixel(part.top_left),
+                    Some(appearance.theme.background)
+                );
+            }
+            assert_eq!(
+                buffer.pixel(block.top_left),
+                Some(appearance.theme.piece(falling.kind()))
+            );
+        }
+    }
+
+    #[test]
+    fn incremental_frames_match_a_full_repaint() {
+        let mut state = GameState::new(3);
+        let mut incremental = PixelRenderer::new(MemoryBuffer::new(FRAME));
+        for actions in [[Action::Left].as_slice(), &[Action::RotateClockwise], &[]]
+            .iter()
+            .cycle()
+            .take(60)
+        {
+            state.step(actions, 250);
+            incremental.draw_frame(&state).unwrap();
+        }
+
+        assert_eq!(incremental.into_target(), render(&state));
+    }
+
+    #[test]
+    fn menus_are_drawn_over_the_game() {
+        let state = GameState::new(0);
+        let mut renderer = PixelRenderer::new(MemoryBuffer::new(FRAME));
+        renderer.draw_frame(&state).unwrap();
+        let lines = ["paused".into(), "p: resume".into()];
+        renderer.draw_menu(&lines).unwrap();
+
+        let layout = *renderer.layout();
+        let buffer = renderer.target();
+        let falling = layout.cell(state.falling_piece().cells()[0]).center();
+        assert_eq!(buffer.pixel(falling), Some(Rgb888::BLACK));
+        assert!(buffer.pixels().contains(&Appearance::default().theme.text));
+
+        renderer.draw_frame(&state).unwrap();
+        assert_eq!(renderer.into_target(), render(&state));
+    }
+
+    #[test]
+    fn new_game_matches_reference() {
+        assert_golden("new_game", &render(&GameState::new(0)));
+    }
+
+    // a few locked pieces, one held piece and the falling piece moved aside
+    fn stacked() -> GameState {
+        let mut state = GameState::new(0);
+        for actions in [
+            [Action::Left; 8].as_slice(),
+            &[Action::RotateClockwise, Action::Right, Action::Right],
+            &[Action::RotateCounterclockwise; 2],
+            &[Action::Right; 6],
+        ] {
+            state.step(actions, 0);
+            // drop the piece until it locks
+            while !state
+                .step(&[Action::SoftDrop], 0)
+                .iter()
+                .any(|event| matches!(event, Event::PieceLocked(_)))
+            {}
+        }
+        state.step(&[Action::Hold, Action::Left, Action::Left], 0);
+        state
+    }
+
+    #[test]
+    fn stacked_pieces_match_reference() {
+        assert_golden("stacked_pieces", &render(&stacked()));
+    }
+
+    #[test]
+    fn hud_matches_reference() {
+        let mut state = stacked();
+        state.step(&[], 1500);
+        let mut renderer = PixelRenderer::new(MemoryBuffer::new(FRAME));
+        renderer.draw_frame(&state).unwrap();
+        let hud = renderer.layout().hud;
+        assert_golden("hud", &renderer.into_target().crop(&hud));
+    }
+
+    #[test]
+    fn styled_blocks_match_reference() {
+        for style in BlockStyle::ALL {
+            let mut renderer = PixelRenderer::new(MemoryBuffer::new(FRAME));
+            renderer.set_appearance(Appearance {
+                block_style: style,
+                grid: true,
+                patterns: style == BlockStyle::Retro,
+                ..Appearance::default()
+            });
+            renderer.draw_frame(&stacked()).unwrap();
+            let board = renderer.layout().board;
+            let frame = renderer.into_target().crop(&board);
+            assert_golden(&format!("style_{}", style.name()), &frame);
+        }
+    }
+}
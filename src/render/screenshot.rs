@@ -0,0 +1,130 @@
+//! Saving what the player sees as a file, e.g. for bug reports.
+//!
+//! A screenshot is the frame a [`Renderer`](super::Renderer) composed last, with running
+//! [animations](super::animation) and menus: the pixels of a
+//! [`PixelRenderer`](super::pixel::PixelRenderer) as an image, or the text of an
+//! [`AnsiRenderer`](super::ansi::AnsiRenderer), which shows the frame again when printed in a
+//! terminal.
+
+use alloc::{format, string::String, vec::Vec};
+
+use super::memory::MemoryBuffer;
+
+/// The image formats screenshots can be saved in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Binary PPM, the smallest header and read by most image tools.
+    #[default]
+    Ppm,
+    /// Uncompressed 24 bit BMP, for tools not reading PPM.
+    Bmp,
+}
+
+impl Format {
+    /// The format with the file extension `name`, e.g. `"bmp"`.
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "ppm" => Some(Self::Ppm),
+            "bmp" => Some(Self::Bmp),
+            _ => None,
+        }
+    }
+
+    /// The file extension of the format, without the dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Ppm => "ppm",
+            Self::Bmp => "bmp",
+        }
+    }
+
+    /// Encodes `image` in this format.
+    pub fn encode(self, image: &MemoryBuffer) -> Vec<u8> {
+        match self {
+            Self::Ppm => image.to_ppm(),
+            Self::Bmp => image.to_bmp(),
+        }
+    }
+}
+
+/// The last frame of a renderer, as it was composed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screenshot<'a> {
+    /// The pixels of the frame.
+    Image(&'a MemoryBuffer),
+    /// The text of the frame, with its ANSI escape sequences.
+    Text(&'a str),
+}
+
+impl Screenshot<'_> {
+    /// The contents of the file, images encoded in `format`.
+    pub fn encode(self, format: Format) -> Vec<u8> {
+        match self {
+            Self::Image(image) => format.encode(image),
+            Self::Text(text) => text.as_bytes().to_vec(),
+        }
+    }
+
+    /// The file extension, without the dot. Images are saved in `format`.
+    pub fn extension(self, format: Format) -> &'static str {
+        match self {
+            Self::Image(_) => format.extension(),
+            Self::Text(_) => "ans",
+        }
+    }
+}
+
+/// The first of `tinytetris-1.<extension>`, `tinytetris-2.<extension>` and so on that does not
+/// `exist` yet, so screenshots of earlier runs are kept.
+pub fn file_name(extension: &str, exists: impl Fn(&str) -> bool) -> String {
+    (1..)
+        .map(|number| format!("tinytetris-{number}.{extension}"))
+        .find(|name| !exists(name))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use embedded_graphics::prelude::Size;
+
+    use super::*;
+    use crate::{
+        GameState,
+        render::{Renderer, ansi::AnsiRenderer, golden::assert_golden, pixel::PixelRenderer},
+    };
+
+    #[test]
+    fn screenshots_show_the_composed_frame() {
+        let state = GameState::new(0);
+        let mut renderer = PixelRenderer::new(MemoryBuffer::new(Size::new(320, 240)));
+        renderer.draw_frame(&state).unwrap();
+        let Some(Screenshot::Image(image)) = renderer.screenshot() else {
+            panic!("pixel renderers take images");
+        };
+        assert_golden("new_game", image);
+        let game = image.clone();
+        assert_eq!(
+            Screenshot::Image(&game).encode(Format::Bmp).len(),
+            54 + 320 * 240 * 3
+        );
+
+        // menus drawn over the game are part of the frame
+        renderer.draw_menu(&["paused".into()]).unwrap();
+        assert_ne!(renderer.screenshot(), Some(Screenshot::Image(&game)));
+
+        let mut renderer = AnsiRenderer::new(String::new());
+        renderer.draw_frame(&state).unwrap();
+        let shot = renderer.screenshot().unwrap();
+        assert_eq!(shot, Screenshot::Text(renderer.writer()));
+        assert_eq!(shot.extension(Format::Bmp), "ans");
+    }
+
+    #[test]
+    fn file_names_skip_existing_files() {
+        let taken = ["tinytetris-1.ppm", "tinytetris-2.ppm", "tinytetris-1.bmp"];
+        let exists = |name: &str| taken.contains(&name);
+        assert_eq!(file_name("ppm", exists), "tinytetris-3.ppm");
+        assert_eq!(file_name("bmp", exists), "tinytetris-2.bmp");
+    }
+}
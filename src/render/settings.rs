@@ -0,0 +1,189 @@
+//! A simple file format for the [`Appearance`] and the [`Backend`], for frontends without command
+//! line arguments.
+//!
+//! Every line sets one setting, settings missing from the file keep their default:
+//!
+//! ```text
+//! # lines starting with '#' are comments
+//! renderer = terminal
+//! theme = classic
+//! patterns = true
+//! style = beveled
+//! grid = false
+//! animations = true
+//! ```
+//!
+//! `theme` takes the name of a built-in theme or the path of a theme file, see
+//! [`theme`](super::theme), `style` the name of a [`BlockStyle`] and `renderer` the name of a
+//! [`Backend`].
+
+use alloc::string::{String, ToString};
+
+use super::{Appearance, style::BlockStyle, theme::Theme};
+use crate::pairs::pairs;
+
+/// The kinds of renderers a frontend can draw with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// A [`PixelRenderer`](super::pixel::PixelRenderer) drawing into the framebuffer.
+    #[default]
+    Framebuffer,
+    /// An [`AnsiRenderer`](super::ansi::AnsiRenderer) writing to the terminal.
+    Terminal,
+}
+
+impl Backend {
+    /// Every backend.
+    pub const ALL: [Backend; 2] = [Backend::Framebuffer, Backend::Terminal];
+
+    /// The name of the backend, as used in settings files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Framebuffer => "framebuffer",
+            Self::Terminal => "terminal",
+        }
+    }
+
+    /// The backend called `name`.
+    pub fn named(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|backend| backend.name() == name)
+    }
+}
+
+/// The settings read from a settings file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Settings {
+    /// What draws the game.
+    pub backend: Backend,
+    /// How the game looks. Its theme is the default while [`theme_file`](Self::theme_file) still
+    /// has to be read.
+    pub appearance: Appearance,
+    /// The path of a theme file to read the theme from, if the theme named none of the built-in
+    /// ones.
+    pub theme_file: Option<String>,
+}
+
+/// The ways a settings file can be invalid, with the line (starting at 1) it happened on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum SettingsError {
+    /// A line is neither empty, a comment nor a `key = value` pair.
+    #[error("line {0}: expected `key = value`")]
+    Syntax(usize),
+    /// A key names no setting.
+    #[error("line {0}: unknown setting")]
+    UnknownKey(usize),
+    /// A value is not one the setting takes.
+    #[error("line {0}: invalid value")]
+    InvalidValue(usize),
+}
+
+impl Settings {
+    /// Parses a settings file as described in the [module docs](self).
+    pub fn parse(text: &str) -> Result<Self, SettingsError> {
+        let mut settings = Self::default();
+        for pair in pairs(text.lines()) {
+            let (number, key, value) = pair.map_err(SettingsError::Syntax)?;
+            let appearance = &mut settings.appearance;
+            let valid = match key {
+                "theme" => {
+                    match Theme::named(value) {
+                        Some(theme) => {
+                            appearance.theme = theme;
+                            settings.theme_file = None;
+                        }
+                        None => {
+                            appearance.theme = Theme::default();
+                            settings.theme_file = Some(value.to_string());
+                        }
+                    }
+                    true
+                }
+                "renderer" => Backend::named(value)
+                    .map(|backend| settings.backend = backend)
+                    .is_some(),
+                "style" => BlockStyle::named(value)
+                    .map(|style| appearance.block_style = style)
+                    .is_some(),
+                "patterns" => set_flag(&mut appearance.patterns, value),
+                "grid" => set_flag(&mut appearance.grid, value),
+                "animations" => set_flag(&mut appearance.animations, value),
+                _ => return Err(SettingsError::UnknownKey(number)),
+            };
+            if !valid {
+                return Err(SettingsError::InvalidValue(number));
+            }
+        }
+        Ok(settings)
+    }
+}
+
+// sets `flag` to a `true` or `false` value, telling whether it was either
+fn set_flag(flag: &mut bool, value: &str) -> bool {
+    match value.parse() {
+        Ok(value) => {
+            *flag = value;
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_override_the_defaults() {
+        let settings = Settings::parse(
+            "# high contrast, no effects\n\
+             theme = high-contrast\n\
+             \n\
+             style=retro\n\
+             animations = false\n\
+             renderer = terminal",
+        )
+        .unwrap();
+        assert_eq!(settings.backend, Backend::Terminal);
+        assert_eq!(
+            settings.appearance,
+            Appearance {
+                theme: Theme::HIGH_CONTRAST,
+                block_style: BlockStyle::Retro,
+                animations: false,
+                ..Appearance::default()
+            }
+        );
+        assert_eq!(settings.theme_file, None);
+        assert_eq!(
+            Settings::parse("theme = themes/dark").unwrap().theme_file,
+            Some("themes/dark".into())
+        );
+    }
+
+    #[test]
+    fn the_last_theme_wins() {
+        let settings = Settings::parse("theme = themes/dark\ntheme = classic").unwrap();
+        assert_eq!(settings.appearance.theme, Theme::CLASSIC);
+        assert_eq!(settings.theme_file, None);
+        let settings = Settings::parse("theme = classic\ntheme = themes/dark").unwrap();
+        assert_eq!(settings.appearance.theme, Theme::default());
+        assert_eq!(settings.theme_file, Some("themes/dark".into()));
+    }
+
+    #[test]
+    fn invalid_lines_are_reported() {
+        assert_eq!(Settings::parse("\ngrid"), Err(SettingsError::Syntax(2)));
+        assert_eq!(
+            Settings::parse("speed = 3"),
+            Err(SettingsError::UnknownKey(1))
+        );
+        assert_eq!(
+            Settings::parse("grid = yes"),
+            Err(SettingsError::InvalidValue(1))
+        );
+        assert_eq!(
+            Settings::parse("style = round"),
+            Err(SettingsError::InvalidValue(1))
+        );
+    }
+}
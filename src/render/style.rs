@@ -0,0 +1,152 @@
+//! The ways a single block can be drawn.
+
+use alloc::{vec, vec::Vec};
+
+use embedded_graphics::{
+    pixelcolor::Rgb888,
+    prelude::{Point, RgbColor, Size},
+    primitives::Rectangle,
+};
+
+/// How blocks are drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BlockStyle {
+    /// A filled square.
+    #[default]
+    Flat,
+    /// A filled square with a lit top left and a shaded bottom right edge.
+    Beveled,
+    /// A bright outline around a dim inside.
+    Outlined,
+    /// A square with a gap to its neighbours and a shine in its corner, like on old consoles.
+    Retro,
+}
+
+impl BlockStyle {
+    /// Every style, for cycling through them in settings.
+    pub const ALL: [BlockStyle; 4] = [
+        BlockStyle::Flat,
+        BlockStyle::Beveled,
+        BlockStyle::Outlined,
+        BlockStyle::Retro,
+    ];
+
+    /// The name of the style, as used on command lines.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Flat => "flat",
+            Self::Beveled => "beveled",
+            Self::Outlined => "outlined",
+            Self::Retro => "retro",
+        }
+    }
+
+    /// The style called `name`.
+    pub fn named(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|style| style.name() == name)
+    }
+
+    /// The rectangles making up a block of `color` covering the square `block`, drawn in order
+    /// on top of each other.
+    pub fn parts(
+        self,
+        block: &Rectangle,
+        color: Rgb888,
+        background: Rgb888,
+    ) -> Vec<(Rectangle, Rgb888)> {
+        let edge = block.size.width;
+        // width of edges and gaps, growing with the block
+        let thin = (edge / 8).max(1);
+        let inset = |by: u32| {
+            Rectangle::new(
+                block.top_left + Point::new(by as i32, by as i32),
+                Size::new(edge.saturating_sub(2 * by), edge.saturating_sub(2 * by)),
+            )
+        };
+        match self {
+            Self::Flat => vec![(*block, color)],
+            Self::Beveled => vec![
+                (*block, blend(color, Rgb888::BLACK, 4)),
+                (
+                    Rectangle::new(block.top_left, Size::new(edge, thin)),
+                    blend(color, Rgb888::WHITE, 4),
+                ),
+                (
+                    Rectangle::new(block.top_left, Size::new(thin, edge)),
+                    blend(color, Rgb888::WHITE, 4),
+                ),
+                (inset(thin), color),
+            ],
+            Self::Outlined => vec![(*block, color), (inset(thin), blend(color, background, 5))],
+            Self::Retro => {
+                let body = Rectangle::new(
+                    block.top_left,
+                    Size::new(edge.saturating_sub(thin), edge.saturating_sub(thin)),
+                );
+                let shine = Size::new(thin.max(edge / 5), thin.max(edge / 5));
+                vec![
+                    (*block, background),
+                    (body, color),
+                    (
+                        Rectangle::new(
+                            block.top_left + Point::new(thin as i32, thin as i32),
+                            shine,
+                        )
+                        .intersection(&body),
+                        blend(color, Rgb888::WHITE, 6),
+                    ),
+                ]
+            }
+        }
+    }
+}
+
+/// `color` moved `eighths` eighths of the way towards `target`.
+pub fn blend(color: Rgb888, target: Rgb888, eighths: u8) -> Rgb888 {
+    let mix = |from: u8, to: u8| {
+        let (from, to) = (from as i32, to as i32);
+        (from + (to - from) * eighths as i32 / 8) as u8
+    };
+    Rgb888::new(
+        mix(color.r(), target.r()),
+        mix(color.g(), target.g()),
+        mix(color.b(), target.b()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_style_stays_inside_its_block() {
+        for edge in [1, 2, 8, 17, 49] {
+            let block = Rectangle::new(Point::new(5, 2), Size::new(edge, edge));
+            for style in BlockStyle::ALL {
+                let parts = style.parts(&block, Rgb888::RED, Rgb888::BLACK);
+                // the first part covers the whole block, so nothing old shines through
+                assert_eq!(parts[0].0, block, "{style:?} {edge}");
+                // tiny blocks leave no room for some parts
+                for (part, _) in parts.iter().filter(|(part, _)| !part.is_zero_sized()) {
+                    assert_eq!(block.intersection(part), *part, "{style:?} {edge}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn styles_can_be_found_by_name() {
+        for style in BlockStyle::ALL {
+            assert_eq!(BlockStyle::named(style.name()), Some(style));
+        }
+        assert_eq!(BlockStyle::named("glossy"), None);
+    }
+
+    #[test]
+    fn blending_moves_towards_the_target() {
+        let gray = Rgb888::new(0x80, 0x80, 0x80);
+        assert_eq!(blend(gray, Rgb888::WHITE, 0), gray);
+        assert_eq!(blend(gray, Rgb888::BLACK, 4), Rgb888::new(0x40, 0x40, 0x40));
+        assert_eq!(blend(Rgb888::BLACK, Rgb888::WHITE, 8), Rgb888::WHITE);
+    }
+}
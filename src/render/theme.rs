@@ -0,0 +1,230 @@
+//! Colors used for drawing, with a few built-in themes and a simple file format for custom ones.
+//!
+//! A theme file starts from a built-in theme and overrides single colors, one per line:
+//!
+//! ```text
+//! # lines starting with '#' are comments
+//! base = monochrome
+//! background = #101010
+//! long = #00ffff
+//! ```
+//!
+//! The keys are `base`, `background`, `border`, `ghost`, `label`, `text` and the pieces `long`,
+//! `quad`, `t`, `z` and `l`. Keys missing from the file keep the color of the base theme, which is
+//! [`Theme::GUIDELINE`] unless given.
+
+use embedded_graphics::pixelcolor::Rgb888;
+
+use crate::{game::piece::PieceKind, pairs::pairs};
+
+/// The colors of everything drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// The colors of the pieces, in the order of [`PieceKind::ALL`].
+    pub pieces: [Rgb888; PieceKind::ALL.len()],
+    /// Empty cells and everything around the board.
+    pub background: Rgb888,
+    /// The border around the board.
+    pub border: Rgb888,
+    /// The ghost showing where the falling piece will lock.
+    pub ghost: Rgb888,
+    /// HUD and panel labels.
+    pub label: Rgb888,
+    /// HUD values.
+    pub text: Rgb888,
+}
+
+/// The ways a theme file can be invalid, with the line (starting at 1) it happened on.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ThemeError {
+    /// A line is neither empty, a comment nor a `key = value` pair.
+    #[error("line {0}: expected `key = value`")]
+    Syntax(usize),
+    /// A key names nothing that has a color.
+    #[error("line {0}: unknown key")]
+    UnknownKey(usize),
+    /// A color is not written as `#rrggbb`.
+    #[error("line {0}: expected a color like #ff8000")]
+    InvalidColor(usize),
+    /// The base names no built-in theme.
+    #[error("line {0}: unknown base theme")]
+    UnknownBase(usize),
+}
+
+const fn rgb(hex: u32) -> Rgb888 {
+    Rgb888::new((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+}
+
+impl Theme {
+    /// The colors of the Tetris guideline.
+    pub const GUIDELINE: Theme = Theme {
+        pieces: [
+            rgb(0x00ffff),
+            rgb(0xffff00),
+            rgb(0xa000f0),
+            rgb(0xf00000),
+            rgb(0xf0a000),
+        ],
+        background: rgb(0x000000),
+        border: rgb(0xffffff),
+        ghost: rgb(0x505050),
+        label: rgb(0x808080),
+        text: rgb(0xffffff),
+    };
+
+    /// The few blues of the NES palette, as in its first levels.
+    pub const CLASSIC: Theme = Theme {
+        pieces: [
+            rgb(0x3cbcfc),
+            rgb(0xfcfcfc),
+            rgb(0x0058f8),
+            rgb(0x3cbcfc),
+            rgb(0x0058f8),
+        ],
+        background: rgb(0x000000),
+        border: rgb(0x7c7c7c),
+        ghost: rgb(0x404040),
+        label: rgb(0xbcbcbc),
+        text: rgb(0xfcfcfc),
+    };
+
+    /// Shades of gray only.
+    pub const MONOCHROME: Theme = Theme {
+        pieces: [
+            rgb(0xe0e0e0),
+            rgb(0xb0b0b0),
+            rgb(0x909090),
+            rgb(0xc8c8c8),
+            rgb(0xa0a0a0),
+        ],
+        background: rgb(0x000000),
+        border: rgb(0xffffff),
+        ghost: rgb(0x404040),
+        label: rgb(0x808080),
+        text: rgb(0xffffff),
+    };
+
+    /// Fully saturated colors on black, for low vision or bad displays.
+    pub const HIGH_CONTRAST: Theme = Theme {
+        pieces: [
+            rgb(0x00ffff),
+            rgb(0xffff00),
+            rgb(0xff00ff),
+            rgb(0xff0000),
+            rgb(0x00ff00),
+        ],
+        background: rgb(0x000000),
+        border: rgb(0xffffff),
+        ghost: rgb(0x808080),
+        label: rgb(0xffffff),
+        text: rgb(0xffff00),
+    };
+
+    /// Every built-in theme with its name.
+    pub const BUILT_IN: [(&str, Theme); 4] = [
+        ("guideline", Self::GUIDELINE),
+        ("classic", Self::CLASSIC),
+        ("monochrome", Self::MONOCHROME),
+        ("high-contrast", Self::HIGH_CONTRAST),
+    ];
+
+    /// The built-in theme called `name`.
+    pub fn named(name: &str) -> Option<Self> {
+        Self::BUILT_IN
+            .iter()
+            .find(|(built_in, _)| *built_in == name)
+            .map(|(_, theme)| *theme)
+    }
+
+    /// Reads a theme file, see the [module docs](self) for the format.
+    pub fn parse(text: &str) -> Result<Self, ThemeError> {
+        let mut theme = Self::GUIDELINE;
+        for pair in pairs(text.lines()) {
+            let (number, key, value) = pair.map_err(ThemeError::Syntax)?;
+            if key == "base" {
+                theme = Self::named(value).ok_or(ThemeError::UnknownBase(number))?;
+                continue;
+            }
+            let color = parse_color(value).ok_or(ThemeError::InvalidColor(number))?;
+            *theme.slot(key).ok_or(ThemeError::UnknownKey(number))? = color;
+        }
+        Ok(theme)
+    }
+
+    /// The color of pieces of `kind`.
+    pub fn piece(&self, kind: PieceKind) -> Rgb888 {
+        self.pieces[kind as usize]
+    }
+
+    fn slot(&mut self, key: &str) -> Option<&mut Rgb888> {
+        let slot = match key {
+            "background" => &mut self.background,
+            "border" => &mut self.border,
+            "ghost" => &mut self.ghost,
+            "label" => &mut self.label,
+            "text" => &mut self.text,
+            "long" => &mut self.pieces[PieceKind::Long as usize],
+            "quad" => &mut self.pieces[PieceKind::Quad as usize],
+            "t" => &mut self.pieces[PieceKind::T as usize],
+            "z" => &mut self.pieces[PieceKind::Z as usize],
+            "l" => &mut self.pieces[PieceKind::L as usize],
+            _ => return None,
+        };
+        Some(slot)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::GUIDELINE
+    }
+}
+
+fn parse_color(value: &str) -> Option<Rgb888> {
+    let hex = value.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok().map(rgb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_override_their_base() {
+        let theme = Theme::parse(
+            "# dark grays\n\
+             base = monochrome\n\
+             \n\
+             background = #101010\n\
+             long=#00FF80",
+        )
+        .unwrap();
+        assert_eq!(theme.background, Rgb888::new(0x10, 0x10, 0x10));
+        assert_eq!(theme.piece(PieceKind::Long), Rgb888::new(0, 0xff, 0x80));
+        assert_eq!(theme.border, Theme::MONOCHROME.border);
+        assert_eq!(Theme::parse(""), Ok(Theme::GUIDELINE));
+    }
+
+    #[test]
+    fn invalid_lines_are_reported() {
+        assert_eq!(Theme::parse("\nborder"), Err(ThemeError::Syntax(2)));
+        assert_eq!(
+            Theme::parse("floor = #000000"),
+            Err(ThemeError::UnknownKey(1))
+        );
+        assert_eq!(Theme::parse("z = red"), Err(ThemeError::InvalidColor(1)));
+        assert_eq!(Theme::parse("z = #12345"), Err(ThemeError::InvalidColor(1)));
+        assert_eq!(Theme::parse("base = neon"), Err(ThemeError::UnknownBase(1)));
+    }
+
+    #[test]
+    fn built_in_themes_can_be_found_by_name() {
+        for (name, theme) in Theme::BUILT_IN {
+            assert_eq!(Theme::named(name), Some(theme));
+        }
+        assert_eq!(Theme::named("neon"), None);
+    }
+}